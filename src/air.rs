@@ -0,0 +1,293 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{self, Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::http::Client;
+use crate::i18n::Locale;
+use crate::open_meteo::{self, group_by_coordinates};
+use crate::Event;
+
+const API_URL: &str = "https://air-quality-api.open-meteo.com/v1/air-quality";
+
+/// The air quality forecast covers the next 5 days only.
+const FORECAST_DAYS: i64 = 5;
+
+/// Grains per m³, only available in Europe.
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct Pollen {
+    pub alder: Option<f64>,
+    pub birch: Option<f64>,
+    pub grass: Option<f64>,
+    pub mugwort: Option<f64>,
+    pub olive: Option<f64>,
+    pub ragweed: Option<f64>,
+}
+
+impl Pollen {
+    fn values(&self) -> [(&'static str, Option<f64>); 6] {
+        [
+            ("alder", self.alder),
+            ("birch", self.birch),
+            ("grass", self.grass),
+            ("mugwort", self.mugwort),
+            ("olive", self.olive),
+            ("ragweed", self.ragweed),
+        ]
+    }
+}
+
+/// Air quality at the event's location and hour (PM2.5 and ozone in μg/m³).
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct AirQuality {
+    pub us_aqi: Option<f64>,
+    pub european_aqi: Option<f64>,
+    pub pm2_5: Option<f64>,
+    pub ozone: Option<f64>,
+    pub pollen: Pollen,
+}
+
+/// Values above which the air quality gets a warning.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Thresholds {
+    pub us_aqi: f64,
+    pub european_aqi: f64,
+    pub pm2_5: f64,
+    pub ozone: f64,
+    pub pollen: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            us_aqi: 100.0,
+            european_aqi: 60.0,
+            pm2_5: 35.0,
+            ozone: 140.0,
+            pollen: 50.0,
+        }
+    }
+}
+
+impl AirQuality {
+    pub fn as_string(&self, thresholds: &Thresholds, locale: &Locale) -> Option<String> {
+        let format = |label: String, value: Option<f64>, unit: &str, threshold: f64| {
+            let value = value?;
+            let warning = if value > threshold { "⚠️ " } else { "" };
+            Some(format!(
+                "{}{} {}{}",
+                warning,
+                locale.t(&label),
+                value.round(),
+                unit
+            ))
+        };
+        let mut values = vec![
+            format("US AQI".to_string(), self.us_aqi, "", thresholds.us_aqi),
+            format(
+                "EU AQI".to_string(),
+                self.european_aqi,
+                "",
+                thresholds.european_aqi,
+            ),
+            format("PM2.5".to_string(), self.pm2_5, "μg/m³", thresholds.pm2_5),
+            format("ozone".to_string(), self.ozone, "μg/m³", thresholds.ozone),
+        ];
+        for (name, value) in self.pollen.values() {
+            if value.is_some_and(|value| value >= 1.0) {
+                values.push(format(
+                    format!("{name} pollen"),
+                    value,
+                    "/m³",
+                    thresholds.pollen,
+                ));
+            }
+        }
+        let values = values.into_iter().flatten().collect::<Vec<String>>();
+        (!values.is_empty()).then(|| values.join(" & "))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Hourly {
+    time: Vec<String>,
+    #[serde(default)]
+    us_aqi: Vec<Option<f64>>,
+    #[serde(default)]
+    european_aqi: Vec<Option<f64>>,
+    #[serde(default)]
+    pm2_5: Vec<Option<f64>>,
+    #[serde(default)]
+    ozone: Vec<Option<f64>>,
+    #[serde(default)]
+    alder_pollen: Vec<Option<f64>>,
+    #[serde(default)]
+    birch_pollen: Vec<Option<f64>>,
+    #[serde(default)]
+    grass_pollen: Vec<Option<f64>>,
+    #[serde(default)]
+    mugwort_pollen: Vec<Option<f64>>,
+    #[serde(default)]
+    olive_pollen: Vec<Option<f64>>,
+    #[serde(default)]
+    ragweed_pollen: Vec<Option<f64>>,
+}
+
+impl Hourly {
+    /// Air quality at the hour closest to the target.
+    fn at(&self, target: NaiveDateTime) -> Option<AirQuality> {
+        let idx = open_meteo::closest(&self.time, target)?;
+        let get = |values: &Vec<Option<f64>>| values.get(idx).copied().flatten();
+        Some(AirQuality {
+            us_aqi: get(&self.us_aqi),
+            european_aqi: get(&self.european_aqi),
+            pm2_5: get(&self.pm2_5),
+            ozone: get(&self.ozone),
+            pollen: Pollen {
+                alder: get(&self.alder_pollen),
+                birch: get(&self.birch_pollen),
+                grass: get(&self.grass_pollen),
+                mugwort: get(&self.mugwort_pollen),
+                olive: get(&self.olive_pollen),
+                ragweed: get(&self.ragweed_pollen),
+            },
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Response {
+    hourly: Hourly,
+}
+
+async fn fetch(
+    coordinates: &[(f64, f64)],
+    start: NaiveDate,
+    end: NaiveDate,
+    client: &Client,
+) -> Result<Vec<Hourly>> {
+    let params = [
+        "us_aqi",
+        "european_aqi",
+        "pm2_5",
+        "ozone",
+        "alder_pollen",
+        "birch_pollen",
+        "grass_pollen",
+        "mugwort_pollen",
+        "olive_pollen",
+        "ragweed_pollen",
+    ]
+    .join(",");
+    let mut query = open_meteo::locations(coordinates).to_vec();
+    query.extend([
+        ("start_date", start.to_string()),
+        ("end_date", end.to_string()),
+        ("timezone", "auto".to_string()),
+        ("hourly", params),
+    ]);
+    let (responses, _) =
+        open_meteo::fetch::<Response>(API_URL, &query, coordinates.len(), client).await?;
+    Ok(responses.into_iter().map(|r| r.hourly).collect())
+}
+
+/// Air quality for each event with a single request, for the events within the range of the air
+/// quality forecast.
+pub async fn for_events(events: &[Event], client: &Client) -> Result<Vec<Option<AirQuality>>> {
+    let in_range = |event: &Event| (0..FORECAST_DAYS).contains(&event.days);
+    let dates = events
+        .iter()
+        .filter(|event| in_range(event))
+        .map(|event| event.when.date());
+    let (Some(start), Some(end)) = (dates.clone().min(), dates.max()) else {
+        return Ok(vec![None; events.len()]);
+    };
+    let (coordinates, indexes) = group_by_coordinates(events);
+    let hourly = fetch(&coordinates, start, end, client).await?;
+    Ok(events
+        .iter()
+        .zip(indexes)
+        .map(|(event, idx)| match in_range(event) {
+            true => hourly[idx].at(event.when),
+            false => None,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOURLY: &str = r#"{
+        "time": ["2021-05-20T08:00", "2021-05-20T09:00"],
+        "us_aqi": [42, 155],
+        "european_aqi": [30, 80],
+        "pm2_5": [8.1, 60.4],
+        "ozone": [61.0, 90.0],
+        "alder_pollen": [null, null],
+        "birch_pollen": [0.0, 0.2],
+        "grass_pollen": [12.0, 70.0],
+        "mugwort_pollen": [null, null],
+        "olive_pollen": [null, null],
+        "ragweed_pollen": [null, null]
+    }"#;
+
+    fn at(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("2021-05-20 {time}"), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_hourly_at() {
+        let hourly: Hourly = serde_json::from_str(HOURLY).unwrap();
+        let air = hourly.at(at("08:10")).unwrap();
+        assert_eq!(air.us_aqi, Some(42.0));
+        assert_eq!(air.pm2_5, Some(8.1));
+        assert_eq!(air.pollen.grass, Some(12.0));
+        assert_eq!(air.pollen.alder, None);
+    }
+
+    #[test]
+    fn test_response() {
+        let body = format!(r#"{{"hourly": {HOURLY}}}"#);
+        let response: Response = serde_json::from_str(&body).unwrap();
+        assert_eq!(response.hourly.time.len(), 2);
+    }
+
+    #[test]
+    fn test_air_quality_as_string() {
+        let hourly: Hourly = serde_json::from_str(HOURLY).unwrap();
+        let thresholds = Thresholds::default();
+        assert_eq!(
+            hourly
+                .at(at("08:00"))
+                .unwrap()
+                .as_string(&thresholds, &Locale::En)
+                .unwrap(),
+            "US AQI 42 & EU AQI 30 & PM2.5 8μg/m³ & ozone 61μg/m³ & grass pollen 12/m³"
+        );
+        assert_eq!(
+            hourly
+                .at(at("09:00"))
+                .unwrap()
+                .as_string(&thresholds, &Locale::En)
+                .unwrap(),
+            "⚠️ US AQI 155 & ⚠️ EU AQI 80 & ⚠️ PM2.5 60μg/m³ & ozone 90μg/m³ & ⚠️ grass pollen 70/m³"
+        );
+        assert_eq!(
+            hourly
+                .at(at("09:00"))
+                .unwrap()
+                .as_string(&thresholds, &Locale::Pt)
+                .unwrap(),
+            "⚠️ IQA EUA 155 & ⚠️ IQA UE 80 & ⚠️ PM2.5 60μg/m³ & ozônio 90μg/m³ & ⚠️ pólen de gramíneas 70/m³"
+        );
+    }
+
+    #[test]
+    fn test_air_quality_without_data() {
+        assert_eq!(
+            AirQuality::default().as_string(&Thresholds::default(), &Locale::En),
+            None
+        );
+    }
+}
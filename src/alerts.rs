@@ -0,0 +1,351 @@
+use chrono::{DateTime, FixedOffset, NaiveDateTime};
+use reqwest::Url;
+use roxmltree::{Document, Node};
+use serde::{self, Serialize};
+
+use crate::course::Point;
+use crate::date_format;
+use crate::error::{Error, Result};
+use crate::http::Client;
+use crate::i18n::Locale;
+use crate::Event;
+
+/// A severe weather warning from a CAP (Common Alerting Protocol) feed, with times in the local
+/// time of the event.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub event: String,
+    pub headline: Option<String>,
+    pub severity: Option<String>,
+    pub area: Option<String>,
+    #[serde(with = "date_format::option")]
+    pub onset: Option<NaiveDateTime>,
+    #[serde(with = "date_format::option")]
+    pub expires: Option<NaiveDateTime>,
+}
+
+impl Alert {
+    pub fn as_string(&self, locale: &Locale) -> String {
+        let mut details = vec![];
+        if let Some(severity) = &self.severity {
+            details.push(locale.t(&severity.to_lowercase()).to_string());
+        }
+        if let Some(expires) = self.expires {
+            details.push(format!("{} {}", locale.t("until"), locale.date(&expires)));
+        }
+        match details.is_empty() {
+            true => self.event.clone(),
+            false => format!("{} ({})", self.event, details.join(", ")),
+        }
+    }
+}
+
+/// One `info` block of a CAP alert, or an Atom entry with CAP elements (as in the NWS feed).
+#[derive(Debug, PartialEq)]
+struct Info {
+    event: String,
+    headline: Option<String>,
+    severity: Option<String>,
+    area: Option<String>,
+    onset: Option<DateTime<FixedOffset>>,
+    expires: Option<DateTime<FixedOffset>>,
+    polygons: Vec<Vec<Point>>,
+    circles: Vec<(Point, f64)>,
+}
+
+fn text<'a>(node: &Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.descendants()
+        .find(|child| child.tag_name().name() == name)
+        .and_then(|child| child.text())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn time(node: &Node, name: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(text(node, name)?).ok()
+}
+
+/// Parses CAP's `latitude,longitude` pairs.
+fn point(value: &str) -> Option<Point> {
+    let (latitude, longitude) = value.split_once(',')?;
+    Some(Point {
+        latitude: latitude.trim().parse().ok()?,
+        longitude: longitude.trim().parse().ok()?,
+    })
+}
+
+fn polygon(value: &str) -> Option<Vec<Point>> {
+    value.split_whitespace().map(point).collect()
+}
+
+/// Parses a CAP circle, a `latitude,longitude` pair and a radius in km.
+fn circle(value: &str) -> Option<(Point, f64)> {
+    let (center, radius) = value.trim().split_once(' ')?;
+    Some((point(center)?, radius.trim().parse().ok()?))
+}
+
+impl Info {
+    fn new(node: &Node) -> Option<Self> {
+        let values = |name: &str| {
+            node.descendants()
+                .filter(|child| child.tag_name().name() == name)
+                .filter_map(|child| child.text())
+                .collect::<Vec<&str>>()
+        };
+        Some(Self {
+            event: text(node, "event")?.to_string(),
+            headline: text(node, "headline")
+                .or_else(|| text(node, "title"))
+                .map(str::to_string),
+            severity: text(node, "severity").map(str::to_string),
+            area: text(node, "areaDesc").map(str::to_string),
+            onset: time(node, "onset").or_else(|| time(node, "effective")),
+            expires: time(node, "expires"),
+            polygons: values("polygon").into_iter().filter_map(polygon).collect(),
+            circles: values("circle").into_iter().filter_map(circle).collect(),
+        })
+    }
+
+    /// Alerts without polygons or circles are assumed to cover the area of the feed.
+    fn covers(&self, location: &Point) -> bool {
+        if self.polygons.is_empty() && self.circles.is_empty() {
+            return true;
+        }
+        self.polygons
+            .iter()
+            .any(|polygon| contains(polygon, location))
+            || self
+                .circles
+                .iter()
+                .any(|(center, radius)| center.distance(location) <= *radius)
+    }
+
+    fn overlaps(&self, start: DateTime<FixedOffset>, end: DateTime<FixedOffset>) -> bool {
+        self.onset.is_none_or(|onset| onset <= end)
+            && self.expires.is_none_or(|expires| expires >= start)
+    }
+
+    fn into_alert(self, offset: FixedOffset) -> Alert {
+        let local = |value: DateTime<FixedOffset>| value.with_timezone(&offset).naive_local();
+        Alert {
+            event: self.event,
+            headline: self.headline,
+            severity: self.severity,
+            area: self.area,
+            onset: self.onset.map(local),
+            expires: self.expires.map(local),
+        }
+    }
+}
+
+/// Whether the point is inside the polygon (ray casting).
+fn contains(polygon: &[Point], location: &Point) -> bool {
+    let mut inside = false;
+    let mut previous = match polygon.last() {
+        Some(point) => point,
+        None => return false,
+    };
+    for point in polygon {
+        if (point.longitude > location.longitude) != (previous.longitude > location.longitude) {
+            let latitude = point.latitude
+                + (location.longitude - point.longitude) / (previous.longitude - point.longitude)
+                    * (previous.latitude - point.latitude);
+            if location.latitude < latitude {
+                inside = !inside;
+            }
+        }
+        previous = point;
+    }
+    inside
+}
+
+/// Parses a CAP alert, or an Atom feed with CAP alerts or CAP elements in its entries. Alerts
+/// with `info` blocks in many languages keep the ones in English, if any.
+fn parse(contents: &str) -> Result<Vec<Info>> {
+    let doc = Document::parse(contents)
+        .map_err(|e| Error::Parse(format!("Failed to parse alerts feed: {e}")))?;
+    let is = |node: &Node, name: &str| node.tag_name().name() == name;
+    let mut infos = vec![];
+    for node in doc.descendants() {
+        if is(&node, "alert") {
+            let blocks = node
+                .children()
+                .filter(|child| is(child, "info"))
+                .collect::<Vec<Node>>();
+            let english = blocks
+                .iter()
+                .filter(|info| text(info, "language").is_some_and(|lang| lang.starts_with("en")))
+                .copied()
+                .collect::<Vec<Node>>();
+            let blocks = if english.is_empty() { blocks } else { english };
+            infos.extend(blocks.iter().filter_map(Info::new));
+        } else if is(&node, "entry") && !node.descendants().any(|child| is(&child, "alert")) {
+            infos.extend(Info::new(&node));
+        }
+    }
+    Ok(infos)
+}
+
+/// Fetches the alerts from the feed that cover the event's location and overlap its window.
+/// `{latitude}` and `{longitude}` in the URL are replaced by the event's coordinates, and
+/// `utc_offset` (in seconds) is the one of the event's location.
+pub async fn for_event(
+    url: &str,
+    event: &Event,
+    utc_offset: i32,
+    client: &Client,
+) -> Result<Vec<Alert>> {
+    let url = url
+        .replace("{latitude}", &event.latitude.to_string())
+        .replace("{longitude}", &event.longitude.to_string());
+    let url = Url::parse(&url)
+        .map_err(|e| Error::Validation(format!("Invalid alerts feed URL {url}: {e}")))?;
+    let (body, _) = client.get_cached(&url).await?;
+    let offset = FixedOffset::east_opt(utc_offset)
+        .ok_or(Error::Parse(format!("Invalid UTC offset {utc_offset}")))?;
+    let window = |value: NaiveDateTime| value.and_local_timezone(offset).single();
+    let (Some(start), Some(end)) = (window(event.when), window(event.end())) else {
+        return Ok(vec![]);
+    };
+    let location = Point {
+        latitude: event.latitude,
+        longitude: event.longitude,
+    };
+    Ok(parse(&body)?
+        .into_iter()
+        .filter(|info| info.covers(&location) && info.overlaps(start, end))
+        .map(|info| info.into_alert(offset))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<alert xmlns="urn:oasis:names:tc:emergency:cap:1.2">
+  <identifier>2.49.0.1.124.1</identifier>
+  <status>Actual</status>
+  <info>
+    <language>fr-CA</language>
+    <event>orages</event>
+    <severity>Severe</severity>
+    <onset>2021-05-20T12:00:00-04:00</onset>
+    <expires>2021-05-20T20:00:00-04:00</expires>
+  </info>
+  <info>
+    <language>en-CA</language>
+    <event>thunderstorm</event>
+    <headline>severe thunderstorm warning in effect</headline>
+    <severity>Severe</severity>
+    <onset>2021-05-20T12:00:00-04:00</onset>
+    <expires>2021-05-20T20:00:00-04:00</expires>
+    <area>
+      <areaDesc>City of Ottawa</areaDesc>
+      <polygon>45.0,-76.0 45.0,-75.0 46.0,-75.0 46.0,-76.0 45.0,-76.0</polygon>
+    </area>
+  </info>
+</alert>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:cap="urn:oasis:names:tc:emergency:cap:1.2">
+  <entry>
+    <title>Heat Advisory issued May 20 by NWS</title>
+    <cap:event>Heat Advisory</cap:event>
+    <cap:effective>2021-05-20T10:00:00-04:00</cap:effective>
+    <cap:expires>2021-05-20T19:00:00-04:00</cap:expires>
+    <cap:severity>Moderate</cap:severity>
+    <cap:areaDesc>Washington</cap:areaDesc>
+    <cap:polygon></cap:polygon>
+  </entry>
+  <entry>
+    <title>Flood Watch</title>
+    <cap:event>Flood Watch</cap:event>
+    <cap:areaDesc>Elsewhere</cap:areaDesc>
+    <cap:circle>40.0,-100.0 10</cap:circle>
+  </entry>
+</feed>"#;
+
+    fn at(value: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_cap() {
+        let infos = parse(CAP).unwrap();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].event, "thunderstorm");
+        assert_eq!(infos[0].area, Some("City of Ottawa".to_string()));
+        assert_eq!(infos[0].polygons[0].len(), 5);
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let infos = parse(ATOM).unwrap();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].event, "Heat Advisory");
+        assert_eq!(
+            infos[0].headline,
+            Some("Heat Advisory issued May 20 by NWS".to_string())
+        );
+        assert_eq!(infos[0].onset, Some(at("2021-05-20T10:00:00-04:00")));
+        assert!(infos[0].polygons.is_empty());
+        assert_eq!(infos[1].circles.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_invalid_feed() {
+        assert!(parse("not xml").is_err());
+    }
+
+    #[test]
+    fn test_info_covers() {
+        let infos = parse(ATOM).unwrap();
+        let ottawa = Point {
+            latitude: 45.42,
+            longitude: -75.7,
+        };
+        let toronto = Point {
+            latitude: 43.65,
+            longitude: -79.38,
+        };
+        assert!(parse(CAP).unwrap()[0].covers(&ottawa));
+        assert!(!parse(CAP).unwrap()[0].covers(&toronto));
+        assert!(infos[0].covers(&toronto));
+        assert!(!infos[1].covers(&toronto));
+    }
+
+    #[test]
+    fn test_info_overlaps() {
+        let info = &parse(CAP).unwrap()[0];
+        assert!(info.overlaps(
+            at("2021-05-20T11:00:00-04:00"),
+            at("2021-05-20T13:00:00-04:00")
+        ));
+        assert!(!info.overlaps(
+            at("2021-05-20T07:00:00-04:00"),
+            at("2021-05-20T09:00:00-04:00")
+        ));
+        assert!(!info.overlaps(
+            at("2021-05-21T07:00:00-04:00"),
+            at("2021-05-21T09:00:00-04:00")
+        ));
+    }
+
+    #[test]
+    fn test_alert_as_string() {
+        let alert = parse(CAP)
+            .unwrap()
+            .pop()
+            .unwrap()
+            .into_alert(FixedOffset::west_opt(4 * 3_600).unwrap());
+        assert_eq!(
+            alert.as_string(&Locale::En),
+            "thunderstorm (severe, until May 20, 20:00)"
+        );
+        assert_eq!(
+            alert.as_string(&Locale::Fr),
+            "thunderstorm (sévère, jusqu'au 20 mai, 20:00)"
+        );
+    }
+}
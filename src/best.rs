@@ -0,0 +1,261 @@
+use chrono::{Duration, NaiveDateTime};
+use serde::{self, Serialize};
+
+use crate::date_format;
+use crate::error::Result;
+use crate::i18n::Locale;
+use crate::units::Units;
+use crate::weather::Weather;
+
+const IDEAL_TEMPERATURE: (f64, f64) = (10.0, 18.0);
+const CALM_WIND: f64 = 10.0;
+const LOW_UV: f64 = 3.0;
+
+/// Comfort penalties for a single hour, in points to subtract from 100.
+#[derive(Debug, Default, PartialEq)]
+struct Penalties {
+    temperature: f64,
+    rain: f64,
+    wind: f64,
+    uv: f64,
+}
+
+impl Penalties {
+    fn new(weather: &Weather) -> Self {
+        let units = &weather.units;
+        let temperature = units.to_celsius(weather.temperature);
+        let (min, max) = IDEAL_TEMPERATURE;
+        Self {
+            temperature: 2.5 * (min - temperature).max(temperature - max).max(0.0),
+            rain: 0.5 * f64::from(weather.probability_of_precipitation)
+                + 10.0 * units.to_mm(weather.precipitation.unwrap_or(0.0)),
+            wind: (units.to_kmh(weather.wind_speed) - CALM_WIND).max(0.0),
+            uv: 4.0 * (weather.uv_index.unwrap_or(0.0) - LOW_UV).max(0.0),
+        }
+    }
+
+    fn total(&self) -> f64 {
+        self.temperature + self.rain + self.wind + self.uv
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.temperature += other.temperature;
+        self.rain += other.rain;
+        self.wind += other.wind;
+        self.uv += other.uv;
+    }
+
+    /// The main drawback, if any is significant, given the temperature in Celsius.
+    fn drawback(&self, temperature: f64) -> Option<&'static str> {
+        let label = if temperature > IDEAL_TEMPERATURE.1 {
+            "warm"
+        } else {
+            "cold"
+        };
+        [
+            (self.temperature, label),
+            (self.rain, "rain likely"),
+            (self.wind, "windy"),
+            (self.uv, "high UV"),
+        ]
+        .into_iter()
+        .filter(|(penalty, _)| *penalty >= 5.0)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, label)| label)
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Slot {
+    #[serde(with = "date_format")]
+    pub start: NaiveDateTime,
+    pub hours: usize,
+    pub score: f64,
+    pub temperature: f64,
+    pub probability_of_precipitation: f64,
+    pub wind_speed: f64,
+    pub uv_index: f64,
+    pub reason: String,
+}
+
+impl Slot {
+    fn new(hours: &[Weather]) -> Self {
+        let count = hours.len() as f64;
+        let mean = |value: &dyn Fn(&Weather) -> f64| hours.iter().map(value).sum::<f64>() / count;
+        let mut penalties = Penalties::default();
+        for weather in hours {
+            penalties.add(&Penalties::new(weather));
+        }
+        let score = (100.0 - penalties.total() / count).max(0.0);
+        let units = &hours[0].units;
+        let temperature = mean(&|w| w.temperature);
+        let reason = match penalties.drawback(units.to_celsius(temperature)) {
+            Some(drawback) => drawback.to_string(),
+            None => "good conditions".to_string(),
+        };
+        Self {
+            start: hours[0].date,
+            hours: hours.len(),
+            score: score.round(),
+            temperature,
+            probability_of_precipitation: mean(&|w| w.probability_of_precipitation.into()),
+            wind_speed: mean(&|w| w.wind_speed),
+            uv_index: mean(&|w| w.uv_index.unwrap_or(0.0)),
+            reason,
+        }
+    }
+
+    fn as_string(&self, rank: usize, units: &Units, locale: &Locale) -> String {
+        format!(
+            "{}. {} ({} {}): {}{}, {}% {}, {}{} {}, UV {} — {}",
+            rank,
+            locale.date(&self.start),
+            locale.t("score"),
+            self.score,
+            self.temperature.round(),
+            units.temperature.symbol(),
+            self.probability_of_precipitation.round(),
+            locale.t("chance of rain"),
+            self.wind_speed.round(),
+            units.speed.symbol(),
+            locale.t("wind"),
+            self.uv_index.round(),
+            locale.t(&self.reason),
+        )
+    }
+}
+
+/// Ranks every start time in the hourly series by the average comfort of the following `hours`,
+/// returning the `top` best ones. Hours missing from the series are gaps no slot spans.
+pub fn best(series: &[Weather], hours: usize, top: usize) -> Vec<Slot> {
+    let mut slots = series
+        .chunk_by(|a, b| b.date - a.date == Duration::hours(1))
+        .flat_map(|consecutive| consecutive.windows(hours.max(1)))
+        .map(Slot::new)
+        .collect::<Vec<Slot>>();
+    slots.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.start.cmp(&b.start)));
+    slots.truncate(top);
+    slots
+}
+
+pub fn as_string(slots: &[Slot], units: &Units, locale: &Locale, json: bool) -> Result<String> {
+    if json {
+        return Ok(serde_json::to_string(slots)?);
+    }
+    Ok(slots
+        .iter()
+        .enumerate()
+        .map(|(idx, slot)| slot.as_string(idx + 1, units, locale))
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stress::Stress;
+    use crate::wind::Wind;
+
+    fn weather<'a>(hour: u32, temperature: f64, rain: i8, wind_speed: f64) -> Weather<'a> {
+        Weather {
+            name: None,
+            location: "Location".to_string(),
+            units: Units::metric(),
+            icon: "☀️",
+            date: NaiveDateTime::parse_from_str(
+                &format!("2021-05-20 {hour}:00:00"),
+                "%Y-%m-%d %H:%M:%S",
+            )
+            .unwrap(),
+            weather_code: 1,
+            description: "mainly clear",
+            probability_of_precipitation: rain,
+            temperature,
+            feels_like: temperature,
+            humidity: 60,
+            wind_speed,
+            wind_direction: 180,
+            wind: Wind::new(180, wind_speed),
+            precipitation: None,
+            wind_gusts: None,
+            uv_index: Some(2.0),
+            cloud_cover: None,
+            dew_point: None,
+            visibility: None,
+            surface_pressure: None,
+            solar_radiation: None,
+            stress: Stress::new(temperature, 60.0, wind_speed, None, &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_penalties_ideal_conditions() {
+        let penalties = Penalties::new(&weather(7, 14.0, 0, 5.0));
+        assert_eq!(penalties, Penalties::default());
+        assert_eq!(penalties.drawback(14.0), None);
+    }
+
+    #[test]
+    fn test_penalties_hot_and_windy() {
+        let penalties = Penalties::new(&weather(14, 28.0, 0, 30.0));
+        assert_eq!(penalties.temperature, 25.0);
+        assert_eq!(penalties.wind, 20.0);
+        assert_eq!(penalties.drawback(28.0), Some("warm"));
+    }
+
+    #[test]
+    fn test_best() {
+        let series = vec![
+            weather(6, 12.0, 0, 5.0),
+            weather(7, 14.0, 0, 5.0),
+            weather(8, 17.0, 60, 5.0),
+            weather(9, 22.0, 80, 15.0),
+        ];
+        let slots = best(&series, 2, 2);
+        assert_eq!(slots.len(), 2);
+        assert_eq!(slots[0].start, series[0].date);
+        assert_eq!(slots[0].score, 100.0);
+        assert_eq!(slots[0].reason, "good conditions");
+        assert_eq!(slots[1].start, series[1].date);
+        assert_eq!(slots[1].reason, "rain likely");
+    }
+
+    #[test]
+    fn test_best_skips_gaps() {
+        let series = vec![
+            weather(6, 12.0, 0, 5.0),
+            weather(7, 14.0, 0, 5.0),
+            weather(9, 14.0, 0, 5.0),
+            weather(11, 14.0, 0, 5.0),
+            weather(12, 14.0, 0, 5.0),
+        ];
+        let starts = best(&series, 2, 10)
+            .iter()
+            .map(|slot| slot.start)
+            .collect::<Vec<NaiveDateTime>>();
+        assert_eq!(starts, vec![series[0].date, series[3].date]);
+    }
+
+    #[test]
+    fn test_best_as_string() {
+        let series = vec![weather(6, 12.0, 0, 5.0)];
+        let slots = best(&series, 1, 3);
+        assert_eq!(
+            as_string(&slots, &Units::metric(), &Locale::En, false).unwrap(),
+            "1. May 20, 06:00 (score 100): 12°C, 0% chance of rain, 5km/h wind, UV 2 — good conditions"
+        );
+        assert_eq!(
+            as_string(&slots, &Units::metric(), &Locale::Fr, false).unwrap(),
+            "1. 20 mai, 06:00 (score 100): 12°C, 0% de risque de pluie, 5km/h vent, UV 2 — bonnes conditions"
+        );
+    }
+}
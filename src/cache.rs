@@ -0,0 +1,137 @@
+use std::{fs, path::PathBuf};
+
+use chrono::{Duration, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::date_format;
+use crate::error::{Error, Result};
+
+const DIR_NAME: &str = ".wed.cache";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Entry {
+    pub url: String,
+    pub body: String,
+
+    #[serde(with = "date_format")]
+    pub fetched_at: NaiveDateTime,
+}
+
+impl Entry {
+    fn is_fresh(&self, max_age: Duration, now: NaiveDateTime) -> bool {
+        now - self.fetched_at < max_age
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    max_age: Duration,
+}
+
+// FNV-1a, so file names are stable across Rust versions (unlike `DefaultHasher`)
+fn key(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}.json")
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, max_age: Duration) -> Self {
+        Self { dir, max_age }
+    }
+
+    pub fn in_home_dir(max_age: Duration) -> Result<Self> {
+        let mut dir =
+            home::home_dir().ok_or(Error::Storage("Couldn't find home directory".to_string()))?;
+        dir.push(DIR_NAME);
+        Ok(Self::new(dir, max_age))
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        self.dir.join(key(url))
+    }
+
+    /// Returns the cached entry for the URL, regardless of its age.
+    pub fn get(&self, url: &str) -> Option<Entry> {
+        let contents = fs::read_to_string(self.path(url)).ok()?;
+        let entry: Entry = serde_json::from_str(&contents).ok()?;
+        if entry.url != url {
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Returns the cached entry for the URL, only if it is within the freshness window.
+    pub fn get_fresh(&self, url: &str) -> Option<Entry> {
+        self.get(url)
+            .filter(|entry| entry.is_fresh(self.max_age, Local::now().naive_local()))
+    }
+
+    pub fn set(&self, url: &str, body: &str) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let entry = Entry {
+            url: url.to_string(),
+            body: body.to_string(),
+            fetched_at: Local::now().naive_local(),
+        };
+        fs::write(self.path(url), serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    const URL: &str = "https://api.open-meteo.com/v1/forecast?latitude=45.4&longitude=-75.7";
+
+    fn create_cache() -> (Cache, TempDir) {
+        let tmp = TempDir::new("wed-test-cache").unwrap();
+        let cache = Cache::new(tmp.path().join(DIR_NAME), Duration::try_hours(1).unwrap());
+        (cache, tmp)
+    }
+
+    #[test]
+    fn test_key_is_stable() {
+        assert_eq!(key(URL), key(URL));
+        assert_ne!(key(URL), key("https://api.open-meteo.com/v1/forecast"));
+        assert!(key(URL).ends_with(".json"));
+    }
+
+    #[test]
+    fn test_cache_miss() {
+        let (cache, tmp) = create_cache();
+        assert_eq!(cache.get(URL), None);
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn test_cache_set_and_get() {
+        let (cache, tmp) = create_cache();
+        cache.set(URL, "{}").unwrap();
+        let entry = cache.get_fresh(URL).unwrap();
+        tmp.close().unwrap();
+
+        assert_eq!(entry.url, URL);
+        assert_eq!(entry.body, "{}");
+    }
+
+    #[test]
+    fn test_cache_stale_entry() {
+        let (cache, tmp) = create_cache();
+        let entry = Entry {
+            url: URL.to_string(),
+            body: "{}".to_string(),
+            fetched_at: Local::now().naive_local() - Duration::try_hours(2).unwrap(),
+        };
+        fs::create_dir_all(&cache.dir).unwrap();
+        fs::write(cache.path(URL), serde_json::to_string(&entry).unwrap()).unwrap();
+
+        assert_eq!(cache.get_fresh(URL), None);
+        assert_eq!(cache.get(URL).unwrap().body, "{}");
+        tmp.close().unwrap();
+    }
+}
@@ -0,0 +1,466 @@
+use serde::{self, Deserialize, Serialize, Serializer};
+
+use crate::error::Result;
+use crate::i18n::Locale;
+use crate::weather::Weather;
+
+const THUNDERSTORM_CODES: std::ops::RangeInclusive<i8> = 95..=99;
+
+/// Warns when the value is above `warn`, and fails when it is above `fail`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limit {
+    pub warn: f64,
+    pub fail: f64,
+}
+
+/// Warns when the value is outside of `warn_min..=warn_max`, and fails when it is outside of
+/// `fail_min..=fail_max`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub warn_min: f64,
+    pub warn_max: f64,
+    pub fail_min: f64,
+    pub fail_max: f64,
+}
+
+/// Race-day thresholds, in Celsius and km/h regardless of the units of the forecast. Values left
+/// out of the configuration keep their defaults.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "PartialThresholds")]
+pub struct Thresholds {
+    pub rain_probability: Limit,
+    pub wind_speed: Limit,
+    pub wind_gusts: Limit,
+    pub temperature: Range,
+    pub thunderstorm: bool,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            rain_probability: Limit {
+                warn: 40.0,
+                fail: 80.0,
+            },
+            wind_speed: Limit {
+                warn: 30.0,
+                fail: 50.0,
+            },
+            wind_gusts: Limit {
+                warn: 50.0,
+                fail: 75.0,
+            },
+            temperature: Range {
+                warn_min: 0.0,
+                warn_max: 28.0,
+                fail_min: -15.0,
+                fail_max: 35.0,
+            },
+            thunderstorm: true,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct PartialLimit {
+    warn: Option<f64>,
+    fail: Option<f64>,
+}
+
+impl PartialLimit {
+    fn or(self, limit: Limit) -> Limit {
+        Limit {
+            warn: self.warn.unwrap_or(limit.warn),
+            fail: self.fail.unwrap_or(limit.fail),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct PartialRange {
+    warn_min: Option<f64>,
+    warn_max: Option<f64>,
+    fail_min: Option<f64>,
+    fail_max: Option<f64>,
+}
+
+impl PartialRange {
+    fn or(self, range: Range) -> Range {
+        Range {
+            warn_min: self.warn_min.unwrap_or(range.warn_min),
+            warn_max: self.warn_max.unwrap_or(range.warn_max),
+            fail_min: self.fail_min.unwrap_or(range.fail_min),
+            fail_max: self.fail_max.unwrap_or(range.fail_max),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct PartialThresholds {
+    rain_probability: PartialLimit,
+    wind_speed: PartialLimit,
+    wind_gusts: PartialLimit,
+    temperature: PartialRange,
+    thunderstorm: Option<bool>,
+}
+
+impl From<PartialThresholds> for Thresholds {
+    fn from(partial: PartialThresholds) -> Self {
+        let defaults = Self::default();
+        Self {
+            rain_probability: partial.rain_probability.or(defaults.rain_probability),
+            wind_speed: partial.wind_speed.or(defaults.wind_speed),
+            wind_gusts: partial.wind_gusts.or(defaults.wind_gusts),
+            temperature: partial.temperature.or(defaults.temperature),
+            thunderstorm: partial.thunderstorm.unwrap_or(defaults.thunderstorm),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Verdict {
+    /// Exit code for the command-line interface, distinct from the ones used for errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Verdict::Pass => 0,
+            Verdict::Warn => 10,
+            Verdict::Fail => 11,
+        }
+    }
+
+    fn label(&self, locale: &Locale) -> String {
+        match self {
+            Verdict::Pass => format!("✅ {}", locale.t("PASS")),
+            Verdict::Warn => format!("⚠️ {}", locale.t("WARN")),
+            Verdict::Fail => format!("⛔ {}", locale.t("FAIL")),
+        }
+    }
+}
+
+/// Why the event warns or fails, in Celsius and km/h.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reason {
+    Above {
+        name: &'static str,
+        value: f64,
+        limit: f64,
+        unit: &'static str,
+    },
+    Outside {
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    Thunderstorm,
+}
+
+impl Reason {
+    pub fn as_string(&self, locale: &Locale) -> String {
+        match self {
+            Reason::Above {
+                name,
+                value,
+                limit,
+                unit,
+            } => format!(
+                "{} {value:.0}{unit} {} {limit}{unit}",
+                locale.t(name),
+                locale.t("is above")
+            ),
+            Reason::Outside { value, min, max } => format!(
+                "{} {value:.0}°C {} {min}°C {} {max}°C",
+                locale.t("temperature"),
+                locale.t("is outside of"),
+                locale.t("to")
+            ),
+            Reason::Thunderstorm => locale.t("thunderstorm expected").to_string(),
+        }
+    }
+}
+
+/// The JSON output has the reasons in English.
+impl Serialize for Reason {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_string(&Locale::En))
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Check<'a> {
+    pub verdict: Verdict,
+    pub reasons: Vec<Reason>,
+    pub weather: &'a Weather<'a>,
+}
+
+fn above(
+    name: &'static str,
+    value: f64,
+    limit: &Limit,
+    unit: &'static str,
+) -> Option<(Verdict, Reason)> {
+    let (verdict, limit) = if value > limit.fail {
+        (Verdict::Fail, limit.fail)
+    } else if value > limit.warn {
+        (Verdict::Warn, limit.warn)
+    } else {
+        return None;
+    };
+    Some((
+        verdict,
+        Reason::Above {
+            name,
+            value,
+            limit,
+            unit,
+        },
+    ))
+}
+
+fn outside(value: f64, range: &Range) -> Option<(Verdict, Reason)> {
+    let (verdict, min, max) = if value < range.fail_min || value > range.fail_max {
+        (Verdict::Fail, range.fail_min, range.fail_max)
+    } else if value < range.warn_min || value > range.warn_max {
+        (Verdict::Warn, range.warn_min, range.warn_max)
+    } else {
+        return None;
+    };
+    Some((verdict, Reason::Outside { value, min, max }))
+}
+
+impl<'a> Check<'a> {
+    pub fn new(weather: &'a Weather<'a>, thresholds: &Thresholds) -> Self {
+        let units = &weather.units;
+        let mut results = vec![
+            above(
+                "rain probability",
+                weather.probability_of_precipitation.into(),
+                &thresholds.rain_probability,
+                "%",
+            ),
+            above(
+                "wind",
+                units.to_kmh(weather.wind_speed),
+                &thresholds.wind_speed,
+                "km/h",
+            ),
+            outside(
+                units.to_celsius(weather.temperature),
+                &thresholds.temperature,
+            ),
+        ];
+        if let Some(gusts) = weather.wind_gusts {
+            results.push(above(
+                "gusts",
+                units.to_kmh(gusts),
+                &thresholds.wind_gusts,
+                "km/h",
+            ));
+        }
+        if thresholds.thunderstorm && THUNDERSTORM_CODES.contains(&weather.weather_code) {
+            results.push(Some((Verdict::Fail, Reason::Thunderstorm)));
+        }
+
+        let results = results.into_iter().flatten().collect::<Vec<_>>();
+        Self {
+            verdict: results
+                .iter()
+                .map(|(verdict, _)| *verdict)
+                .max()
+                .unwrap_or(Verdict::Pass),
+            reasons: results.into_iter().map(|(_, reason)| reason).collect(),
+            weather,
+        }
+    }
+
+    pub fn as_string(&self, locale: &Locale, json: bool) -> Result<String> {
+        if json {
+            return Ok(serde_json::to_string(&self)?);
+        }
+        let name = self
+            .weather
+            .name
+            .as_deref()
+            .unwrap_or(&self.weather.location);
+        let mut lines = vec![format!("{} {}", self.verdict.label(locale), name)];
+        lines.extend(
+            self.reasons
+                .iter()
+                .map(|reason| format!("- {}", reason.as_string(locale))),
+        );
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stress::Stress;
+    use crate::units::Units;
+    use crate::wind::Wind;
+    use crate::wmo;
+    use chrono::NaiveDateTime;
+
+    fn weather<'a>(
+        weather_code: i8,
+        probability_of_precipitation: i8,
+        temperature: f64,
+        wind_speed: f64,
+        wind_gusts: Option<f64>,
+    ) -> Weather<'a> {
+        Weather {
+            name: Some("Marathon".to_string()),
+            location: "Location".to_string(),
+            units: Units::metric(),
+            icon: "☀️",
+            date: NaiveDateTime::parse_from_str("2021-05-20 8:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
+            weather_code,
+            description: wmo::description(weather_code),
+            probability_of_precipitation,
+            temperature,
+            feels_like: temperature,
+            humidity: 60,
+            wind_speed,
+            wind_direction: 180,
+            wind: Wind::new(180, wind_speed),
+            precipitation: None,
+            wind_gusts,
+            uv_index: None,
+            cloud_cover: None,
+            dew_point: None,
+            visibility: None,
+            surface_pressure: None,
+            solar_radiation: None,
+            stress: Stress::new(temperature, 60.0, wind_speed, None, &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
+        }
+    }
+
+    fn reasons(check: &Check) -> Vec<String> {
+        check
+            .reasons
+            .iter()
+            .map(|reason| reason.as_string(&Locale::En))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_pass() {
+        let weather = weather(1, 10, 15.0, 10.0, Some(20.0));
+        let check = Check::new(&weather, &Thresholds::default());
+        assert_eq!(check.verdict, Verdict::Pass);
+        assert!(check.reasons.is_empty());
+        assert_eq!(
+            check.as_string(&Locale::En, false).unwrap(),
+            "✅ PASS Marathon"
+        );
+    }
+
+    #[test]
+    fn test_check_warn() {
+        let weather = weather(61, 50, 15.0, 10.0, Some(55.0));
+        let check = Check::new(&weather, &Thresholds::default());
+        assert_eq!(check.verdict, Verdict::Warn);
+        assert_eq!(
+            check.as_string(&Locale::En, false).unwrap(),
+            [
+                "⚠️ WARN Marathon",
+                "- rain probability 50% is above 40%",
+                "- gusts 55km/h is above 50km/h",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            check.as_string(&Locale::Fr, false).unwrap(),
+            [
+                "⚠️ ATTENTION Marathon",
+                "- probabilité de pluie 50% dépasse 40%",
+                "- rafales 55km/h dépasse 50km/h",
+            ]
+            .join("\n")
+        );
+        assert!(check
+            .as_string(&Locale::Fr, true)
+            .unwrap()
+            .contains(r#""reasons":["rain probability 50% is above 40%","#));
+    }
+
+    #[test]
+    fn test_check_fail_on_thunderstorm() {
+        let weather = weather(95, 30, 22.0, 10.0, None);
+        let check = Check::new(&weather, &Thresholds::default());
+        assert_eq!(check.verdict, Verdict::Fail);
+        assert_eq!(reasons(&check), vec!["thunderstorm expected"]);
+    }
+
+    #[test]
+    fn test_check_fail_on_temperature() {
+        let weather = weather(1, 0, 36.0, 10.0, None);
+        let check = Check::new(&weather, &Thresholds::default());
+        assert_eq!(check.verdict, Verdict::Fail);
+        assert_eq!(
+            reasons(&check),
+            vec!["temperature 36°C is outside of -15°C to 35°C"]
+        );
+    }
+
+    #[test]
+    fn test_check_with_imperial_units() {
+        let mut weather = weather(1, 0, 95.0, 20.0, None);
+        weather.units = Units::imperial();
+        let check = Check::new(&weather, &Thresholds::default());
+        assert_eq!(check.verdict, Verdict::Warn);
+        assert_eq!(
+            reasons(&check),
+            vec![
+                "wind 32km/h is above 30km/h",
+                "temperature 35°C is outside of 0°C to 28°C"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_thresholds_from_partial_config() {
+        let thresholds: Thresholds = serde_json::from_str(
+            r#"{"wind_speed": {"warn": 20}, "temperature": {"fail_max": 32}}"#,
+        )
+        .unwrap();
+        let defaults = Thresholds::default();
+        assert_eq!(
+            thresholds.wind_speed,
+            Limit {
+                warn: 20.0,
+                fail: defaults.wind_speed.fail
+            }
+        );
+        assert_eq!(thresholds.temperature.fail_max, 32.0);
+        assert_eq!(
+            thresholds.temperature.warn_max,
+            defaults.temperature.warn_max
+        );
+        assert_eq!(thresholds.rain_probability, defaults.rain_probability);
+    }
+
+    #[test]
+    fn test_verdict_exit_codes() {
+        assert_eq!(Verdict::Pass.exit_code(), 0);
+        assert_ne!(Verdict::Warn.exit_code(), Verdict::Fail.exit_code());
+    }
+}
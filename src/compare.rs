@@ -0,0 +1,260 @@
+use serde::{self, Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::http::Client;
+use crate::i18n::Locale;
+use crate::output::{self, Options};
+use crate::units::Units;
+use crate::weather::Weather;
+use crate::Event;
+
+/// Differences between the models above which they are flagged as diverging, in Celsius, percent
+/// and km/h regardless of the units of the forecast.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Divergence {
+    pub temperature: f64,
+    pub rain_probability: f64,
+    pub wind_speed: f64,
+}
+
+impl Default for Divergence {
+    fn default() -> Self {
+        Self {
+            temperature: 3.0,
+            rain_probability: 30.0,
+            wind_speed: 10.0,
+        }
+    }
+}
+
+/// The forecast for the event from one weather model.
+#[derive(Serialize, Debug)]
+pub struct Model<'a> {
+    pub model: String,
+    #[serde(flatten)]
+    pub weather: Weather<'a>,
+}
+
+/// Fetches the event hour from each of the models (e.g. `ecmwf_ifs025`, `gfs_seamless`) with a
+/// single request.
+pub async fn compare<'a>(
+    event: &Event,
+    models: &[String],
+    units: &Units,
+    client: &Client,
+) -> Result<Vec<Model<'a>>> {
+    let forecasts = Weather::for_models(event, models, units, client).await?;
+    Ok(models
+        .iter()
+        .zip(forecasts)
+        .map(|(model, weather)| Model {
+            model: model.clone(),
+            weather,
+        })
+        .collect())
+}
+
+fn difference(values: &[f64]) -> f64 {
+    let max = values.iter().copied().fold(f64::MIN, f64::max);
+    let min = values.iter().copied().fold(f64::MAX, f64::min);
+    max - min
+}
+
+/// Difference between the highest and the lowest value, when it is above the threshold once the
+/// values are converted with `metric`.
+fn spread(values: &[f64], metric: impl Fn(f64) -> f64, threshold: f64) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let converted = values
+        .iter()
+        .map(|value| metric(*value))
+        .collect::<Vec<f64>>();
+    (difference(&converted) > threshold).then(|| difference(values))
+}
+
+/// Fields where the models diverge beyond the thresholds, with the difference between them.
+pub fn divergent(models: &[Model], divergence: &Divergence, locale: &Locale) -> Vec<String> {
+    let Some(first) = models.first() else {
+        return vec![];
+    };
+    let units = &first.weather.units;
+    let (temperature, speed) = (units.temperature.symbol(), units.speed.symbol());
+    let values = |value: fn(&Weather) -> f64| {
+        models
+            .iter()
+            .map(|model| value(&model.weather))
+            .collect::<Vec<f64>>()
+    };
+    let mut fields = vec![];
+    if let Some(value) = spread(
+        &values(|weather| weather.temperature),
+        |value| units.to_celsius(value),
+        divergence.temperature,
+    ) {
+        fields.push(format!(
+            "{} ({}{})",
+            locale.t("temperature"),
+            value.round(),
+            temperature
+        ));
+    }
+    if let Some(value) = spread(
+        &values(|weather| weather.probability_of_precipitation.into()),
+        |value| value,
+        divergence.rain_probability,
+    ) {
+        fields.push(format!("{} ({}%)", locale.t("rain"), value.round()));
+    }
+    if let Some(value) = spread(
+        &values(|weather| weather.wind_speed),
+        |value| units.to_kmh(value),
+        divergence.wind_speed,
+    ) {
+        fields.push(format!("{} ({}{})", locale.t("wind"), value.round(), speed));
+    }
+    fields
+}
+
+/// Compact table with one row per model, followed by the fields where the models diverge.
+pub fn as_string(models: &[Model], json: bool, options: &Options) -> Result<String> {
+    if json {
+        return Ok(serde_json::to_string(models)?);
+    }
+    let Some(first) = models.first() else {
+        return Ok("".to_string());
+    };
+    let notification = first.weather.as_notification(options)?;
+    let (temperature, speed) = (
+        first.weather.units.temperature.symbol(),
+        first.weather.units.speed.symbol(),
+    );
+    let locale = &options.locale;
+    let mut rows = vec![[
+        locale.t("model").to_string(),
+        locale.t("temperature").to_string(),
+        locale.t("feels like").to_string(),
+        locale.t("rain").to_string(),
+        locale.t("wind").to_string(),
+        "".to_string(),
+    ]];
+    for model in models {
+        let weather = &model.weather;
+        rows.push([
+            model.model.clone(),
+            format!("{}{}", weather.temperature.round(), temperature),
+            format!("{}{}", weather.feels_like.round(), temperature),
+            format!("{}%", weather.probability_of_precipitation),
+            format!(
+                "{}{} {}",
+                weather.wind_speed.round(),
+                speed,
+                options.wind.as_string(&weather.wind, locale)
+            ),
+            weather.icon.to_string(),
+        ]);
+    }
+    let mut lines = vec![format!("{} {}", notification.title, notification.subtitle)];
+    lines.extend(output::align(&rows));
+    let fields = divergent(models, &options.divergence, locale);
+    if !fields.is_empty() {
+        lines.push(format!(
+            "⚠️ {} {}",
+            locale.t("models diverge on"),
+            fields.join(" & ")
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stress::Stress;
+    use crate::wind::Wind;
+    use chrono::NaiveDateTime;
+
+    fn model<'a>(name: &str, temperature: f64, rain: i8, wind_speed: f64) -> Model<'a> {
+        Model {
+            model: name.to_string(),
+            weather: Weather {
+                name: Some("Event".to_string()),
+                location: "Location".to_string(),
+                units: Units::metric(),
+                icon: "☀️",
+                date: NaiveDateTime::parse_from_str("2021-05-20 08:00", "%Y-%m-%d %H:%M").unwrap(),
+                weather_code: 1,
+                description: "mainly clear",
+                probability_of_precipitation: rain,
+                temperature,
+                feels_like: temperature,
+                humidity: 60,
+                wind_speed,
+                wind_direction: 180,
+                wind: Wind::new(180, wind_speed),
+                precipitation: None,
+                wind_gusts: None,
+                uv_index: None,
+                cloud_cover: None,
+                dew_point: None,
+                visibility: None,
+                surface_pressure: None,
+                solar_radiation: None,
+                stress: Stress::new(temperature, 60.0, wind_speed, None, &Units::metric()),
+                recommendations: vec![],
+                course: vec![],
+                checkpoints: vec![],
+                daylight: None,
+                air_quality: None,
+                alerts: vec![],
+                ensemble: None,
+                as_of: None,
+                utc_offset: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_divergent() {
+        let models = vec![
+            model("ecmwf_ifs025", 17.0, 10, 9.0),
+            model("gfs_seamless", 21.0, 30, 12.0),
+        ];
+        assert_eq!(
+            divergent(&models, &Divergence::default(), &Locale::En),
+            vec!["temperature (4°C)"]
+        );
+        assert_eq!(
+            divergent(&models, &Divergence::default(), &Locale::Fr),
+            vec!["température (4°C)"]
+        );
+        assert!(divergent(&models[..1], &Divergence::default(), &Locale::En).is_empty());
+    }
+
+    #[test]
+    fn test_as_string() {
+        let models = vec![
+            model("ecmwf_ifs025", 17.0, 10, 9.0),
+            model("gfs_seamless", 21.0, 50, 22.0),
+        ];
+        assert_eq!(
+            as_string(&models, false, &Options::default()).unwrap(),
+            [
+                "🗓️ Event (May 20, 08:00) 🌐 Location",
+                "model         temperature  feels like  rain  wind",
+                "ecmwf_ifs025  17°C         17°C        10%   9km/h S   ☀️",
+                "gfs_seamless  21°C         21°C        50%   22km/h S  ☀️",
+                "⚠️ models diverge on temperature (4°C) & rain (40%) & wind (13km/h)",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_as_json() {
+        let models = vec![model("ecmwf_ifs025", 17.0, 10, 9.0)];
+        let json = as_string(&models, true, &Options::default()).unwrap();
+        assert!(json.starts_with(r#"[{"model":"ecmwf_ifs025","name":"Event","#));
+    }
+}
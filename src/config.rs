@@ -0,0 +1,215 @@
+use std::{collections::HashMap, fs::File, io::BufReader, path::PathBuf, time::Duration};
+
+use serde::Deserialize;
+
+use crate::air;
+use crate::check::Thresholds;
+use crate::compare::Divergence;
+use crate::error::{Error, Result};
+use crate::i18n::Locale;
+use crate::wind;
+
+const FILE_NAME: &str = ".wed.config.json";
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    /// Contact e-mail added to the User-Agent of HTTP requests, as per Nominatim's usage policy
+    pub email: Option<String>,
+
+    pub http: HttpConfig,
+
+    pub cache: CacheConfig,
+
+    /// Thresholds for `wed check`
+    pub check: Thresholds,
+
+    /// Thresholds above which the air quality gets a warning
+    pub air_quality: air::Thresholds,
+
+    /// URL of a CAP/Atom feed with severe weather alerts, `{latitude}` and `{longitude}` are
+    /// replaced by the event's coordinates
+    pub alerts: Option<String>,
+
+    /// Differences above which weather models are flagged as diverging with `--models`
+    pub divergence: Divergence,
+
+    /// Language of the human-readable output, instead of the one from `LANG`
+    pub locale: Option<Locale>,
+
+    /// Compass points, arrow and Beaufort force in the human-readable wind description
+    pub wind: wind::Style,
+
+    /// Named templates to use with `--template`
+    pub templates: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Seconds to wait for a connection to be established
+    pub connect_timeout: u64,
+
+    /// Seconds to wait for data once connected
+    pub read_timeout: u64,
+
+    /// Number of attempts after a server or network error
+    pub retries: u8,
+
+    /// Milliseconds to wait before the first retry, doubled at every subsequent retry
+    pub backoff: u64,
+
+    /// Proxy URL for all requests (e.g. `http://localhost:8080` or `socks5://localhost:1080`)
+    pub proxy: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: 10,
+            read_timeout: 30,
+            retries: 3,
+            backoff: 1_000,
+            proxy: None,
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout)
+    }
+
+    pub fn read_timeout(&self) -> Duration {
+        Duration::from_secs(self.read_timeout)
+    }
+
+    pub fn backoff(&self, attempt: u8) -> Duration {
+        Duration::from_millis(
+            self.backoff
+                .saturating_mul(2u64.saturating_pow(attempt.into())),
+        )
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Seconds during which a cached forecast is used instead of a new request
+    pub max_age: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { max_age: 3_600 }
+    }
+}
+
+impl CacheConfig {
+    pub fn max_age(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.max_age.try_into().unwrap_or(i64::MAX))
+    }
+}
+
+fn default_config_path() -> Result<PathBuf> {
+    let mut path =
+        home::home_dir().ok_or(Error::Storage("Couldn't find home directory".to_string()))?;
+    path.push(FILE_NAME);
+    Ok(path)
+}
+
+impl Config {
+    pub fn from_file_path(path: &PathBuf) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn from_file() -> Result<Self> {
+        Self::from_file_path(&default_config_path()?)
+    }
+
+    pub fn user_agent(&self) -> String {
+        let contact = match &self.email {
+            Some(email) => format!("{}; {}", env!("CARGO_PKG_REPOSITORY"), email),
+            None => env!("CARGO_PKG_REPOSITORY").to_string(),
+        };
+        format!(
+            "{}/{} ({})",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            contact,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_config_from_missing_file() {
+        let tmp = TempDir::new("wed-test-config").unwrap();
+        let config = Config::from_file_path(&tmp.path().join(FILE_NAME)).unwrap();
+        tmp.close().unwrap();
+
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_config_from_file_path() {
+        let tmp = TempDir::new("wed-test-config").unwrap();
+        let path = tmp.path().join(FILE_NAME);
+        File::create(&path)
+            .unwrap()
+            .write_all(b"{\"email\":\"runner@example.com\"}")
+            .unwrap();
+        let config = Config::from_file_path(&path).unwrap();
+        tmp.close().unwrap();
+
+        assert_eq!(config.email, Some("runner@example.com".to_string()));
+        assert_eq!(config.http, HttpConfig::default());
+        assert_eq!(config.cache, CacheConfig::default());
+        assert_eq!(config.check, Thresholds::default());
+    }
+
+    #[test]
+    fn test_http_config_backoff() {
+        let config = HttpConfig::default();
+        assert_eq!(config.backoff(0), Duration::from_secs(1));
+        assert_eq!(config.backoff(1), Duration::from_secs(2));
+        assert_eq!(config.backoff(2), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_config_user_agent_without_email() {
+        let config = Config::default();
+        assert_eq!(
+            config.user_agent(),
+            format!(
+                "wed/{} (https://github.com/cuducos/wed)",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn test_config_user_agent_with_email() {
+        let config = Config {
+            email: Some("runner@example.com".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.user_agent(),
+            format!(
+                "wed/{} (https://github.com/cuducos/wed; runner@example.com)",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+}
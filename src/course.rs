@@ -0,0 +1,466 @@
+use std::{fs, path::Path};
+
+use chrono::{Duration, NaiveDateTime};
+use roxmltree::{Document, Node};
+use serde::{self, Deserialize, Serialize};
+
+use crate::date_format;
+use crate::error::{Error, Result};
+use crate::i18n::Locale;
+use crate::units::{round, Units};
+use crate::weather::Weather;
+
+const EARTH_RADIUS: f64 = 6_371.0;
+
+/// Distance between waypoints sampled along the course, in km.
+const SAMPLE_INTERVAL: f64 = 5.0;
+
+/// Wind components for a leg of the course heading to `bearing` (degrees clockwise from north):
+/// `headwind` is negative for a tailwind, and `crosswind` is positive when the wind comes from
+/// the right-hand side.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub bearing: f64,
+    pub headwind: f64,
+    pub crosswind: f64,
+}
+
+impl Segment {
+    /// Wind direction is where the wind comes from, as in the forecast. The components are worked
+    /// out in km/h, since the Beaufort scale is not linear.
+    pub fn new(bearing: f64, wind_speed: f64, wind_direction: i32, units: &Units) -> Self {
+        let bearing = bearing.rem_euclid(360.0);
+        let angle = (f64::from(wind_direction) - bearing).to_radians();
+        let wind_speed = units.to_kmh(wind_speed);
+        Self {
+            bearing,
+            headwind: round(units.from_kmh(wind_speed * angle.cos()), 1),
+            crosswind: round(units.from_kmh(wind_speed * angle.sin()), 1),
+        }
+    }
+
+    pub fn as_string(&self, units: &Units, locale: &Locale) -> String {
+        let speed = units.speed.symbol();
+        let along = if self.headwind < 0.0 {
+            "tailwind"
+        } else {
+            "headwind"
+        };
+        let side = if self.crosswind < 0.0 {
+            "crosswind from the left"
+        } else {
+            "crosswind from the right"
+        };
+        format!(
+            "{}°: {}{} {} & {}{} {}",
+            self.bearing.round(),
+            self.headwind.abs().round(),
+            speed,
+            locale.t(along),
+            self.crosswind.abs().round(),
+            speed,
+            locale.t(side)
+        )
+    }
+}
+
+pub fn segments(
+    bearings: &[f64],
+    wind_speed: f64,
+    wind_direction: i32,
+    units: &Units,
+) -> Vec<Segment> {
+    bearings
+        .iter()
+        .map(|bearing| Segment::new(*bearing, wind_speed, wind_direction, units))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Point {
+    pub(crate) latitude: f64,
+    pub(crate) longitude: f64,
+}
+
+impl Point {
+    /// Great-circle distance in km (haversine formula).
+    pub(crate) fn distance(&self, other: &Self) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.longitude - self.longitude).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().asin()
+    }
+
+    /// Initial bearing towards the other point, in degrees clockwise from north.
+    fn bearing(&self, other: &Self) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlon = (other.longitude - self.longitude).to_radians();
+        let y = dlon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+}
+
+/// A point sampled along the course, `distance` km from the start, heading to `bearing`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Waypoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub distance: f64,
+    pub bearing: f64,
+}
+
+fn coordinate(node: &Node, name: &str) -> Result<f64> {
+    let value = node
+        .attribute(name)
+        .map(str::to_string)
+        .or_else(|| {
+            node.descendants()
+                .find(|child| child.tag_name().name() == name)
+                .and_then(|child| child.text())
+                .map(str::to_string)
+        })
+        .ok_or(Error::Parse(format!("Track point without {name}")))?;
+    Ok(value.trim().parse::<f64>()?)
+}
+
+/// Reads the track points of a GPX (`trkpt` or `rtept`) or TCX (`Trackpoint`) file.
+fn parse(contents: &str) -> Result<Vec<Point>> {
+    let doc = Document::parse(contents)
+        .map_err(|e| Error::Parse(format!("Failed to parse course file: {e}")))?;
+    doc.descendants()
+        .filter(|node| match node.tag_name().name() {
+            "trkpt" | "rtept" => true,
+            "Trackpoint" => node
+                .children()
+                .any(|child| child.tag_name().name() == "Position"),
+            _ => false,
+        })
+        .map(|node| {
+            let (latitude, longitude) = match node.tag_name().name() {
+                "Trackpoint" => (
+                    coordinate(&node, "LatitudeDegrees")?,
+                    coordinate(&node, "LongitudeDegrees")?,
+                ),
+                _ => (coordinate(&node, "lat")?, coordinate(&node, "lon")?),
+            };
+            Ok(Point {
+                latitude,
+                longitude,
+            })
+        })
+        .collect()
+}
+
+/// Picks the first track point at every `interval` km, and the finish.
+fn sample(points: &[Point], interval: f64) -> Result<Vec<Waypoint>> {
+    if points.len() < 2 {
+        return Err(Error::Validation(
+            "The course needs at least two track points".to_string(),
+        ));
+    }
+    let mut distance = 0.0;
+    let mut sampled: Vec<(Point, f64)> = vec![(points[0], 0.0)];
+    for pair in points.windows(2) {
+        distance += pair[0].distance(&pair[1]);
+        if distance >= sampled.len() as f64 * interval {
+            sampled.push((pair[1], distance));
+        }
+    }
+    if sampled.len() == 1 || sampled[sampled.len() - 1].1 < distance {
+        sampled.push((points[points.len() - 1], distance));
+    }
+    let bearings = sampled
+        .windows(2)
+        .map(|pair| pair[0].0.bearing(&pair[1].0))
+        .collect::<Vec<f64>>();
+    Ok(sampled
+        .iter()
+        .enumerate()
+        .map(|(idx, (point, distance))| Waypoint {
+            latitude: point.latitude,
+            longitude: point.longitude,
+            distance: round(*distance, 1),
+            bearing: round(bearings[idx.min(bearings.len() - 1)], 1),
+        })
+        .collect())
+}
+
+/// Samples a waypoint every 5 km along the track of a GPX or TCX file.
+pub fn from_file(path: &Path) -> Result<Vec<Waypoint>> {
+    sample(&parse(&fs::read_to_string(path)?)?, SAMPLE_INTERVAL)
+}
+
+/// Estimated time of arrival at the waypoint, for a speed in km/h, or none without a speed above
+/// zero.
+pub fn arrival(start: NaiveDateTime, waypoint: &Waypoint, speed: f64) -> Option<NaiveDateTime> {
+    if !(speed.is_finite() && speed > 0.0) {
+        return None;
+    }
+    let seconds = Duration::try_seconds((waypoint.distance / speed * 3_600.0).round() as i64)?;
+    start.checked_add_signed(seconds)
+}
+
+/// Conditions at a waypoint at the estimated time of arrival.
+#[derive(Serialize, Debug)]
+pub struct Checkpoint<'a> {
+    pub distance: f64,
+    pub latitude: f64,
+    pub longitude: f64,
+
+    #[serde(with = "date_format")]
+    pub arrival: NaiveDateTime,
+    pub icon: &'a str,
+    pub weather_code: i8,
+    pub description: &'a str,
+    pub temperature: f64,
+    pub feels_like: f64,
+    pub probability_of_precipitation: i8,
+    pub wind_speed: f64,
+    pub wind_direction: i32,
+    pub wind: Segment,
+}
+
+impl<'a> Checkpoint<'a> {
+    /// The weather is the forecast for the hour closest to the `arrival`.
+    pub fn new(waypoint: &Waypoint, arrival: NaiveDateTime, weather: &Weather<'a>) -> Self {
+        Self {
+            distance: round(weather.units.from_km(waypoint.distance), 1),
+            latitude: waypoint.latitude,
+            longitude: waypoint.longitude,
+            arrival,
+            icon: weather.icon,
+            weather_code: weather.weather_code,
+            description: weather.description,
+            temperature: weather.temperature,
+            feels_like: weather.feels_like,
+            probability_of_precipitation: weather.probability_of_precipitation,
+            wind_speed: weather.wind_speed,
+            wind_direction: weather.wind_direction,
+            wind: Segment::new(
+                waypoint.bearing,
+                weather.wind_speed,
+                weather.wind_direction,
+                &weather.units,
+            ),
+        }
+    }
+
+    pub fn as_string(&self, units: &Units, locale: &Locale) -> String {
+        format!(
+            "{}{} {} {} {} {}{}, {}% {}, {}",
+            self.distance,
+            units.distance.symbol(),
+            locale.t("at"),
+            self.arrival.format("%H:%M"),
+            self.icon,
+            self.temperature.round(),
+            units.temperature.symbol(),
+            self.probability_of_precipitation,
+            locale.t("chance of rain"),
+            self.wind.as_string(units, locale),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::Speed;
+    use crate::wind::Wind;
+
+    #[test]
+    fn test_segment_headwind() {
+        let segment = Segment::new(180.0, 20.0, 180, &Units::metric());
+        assert_eq!(segment.headwind, 20.0);
+        assert_eq!(segment.crosswind, 0.0);
+    }
+
+    #[test]
+    fn test_segment_tailwind() {
+        let segment = Segment::new(0.0, 20.0, 180, &Units::metric());
+        assert_eq!(segment.headwind, -20.0);
+        assert_eq!(segment.crosswind, 0.0);
+    }
+
+    #[test]
+    fn test_segment_crosswind_from_the_right() {
+        let segment = Segment::new(0.0, 20.0, 90, &Units::metric());
+        assert_eq!(segment.headwind, 0.0);
+        assert_eq!(segment.crosswind, 20.0);
+    }
+
+    #[test]
+    fn test_segment_normalizes_bearing() {
+        let segment = Segment::new(-90.0, 10.0, 315, &Units::metric());
+        assert_eq!(segment.bearing, 270.0);
+        assert_eq!(segment.headwind, 7.1);
+        assert_eq!(segment.crosswind, 7.1);
+    }
+
+    #[test]
+    fn test_segment_in_beaufort() {
+        let units = Units {
+            speed: Speed::Beaufort,
+            ..Units::metric()
+        };
+        let segment = Segment::new(0.0, 5.0, 135, &units);
+        assert_eq!(segment.headwind, -3.9);
+        assert_eq!(segment.crosswind, 3.9);
+    }
+
+    #[test]
+    fn test_segment_as_string() {
+        let segment = Segment::new(90.0, 10.0, 45, &Units::metric());
+        assert_eq!(
+            segment.as_string(&Units::metric(), &Locale::En),
+            "90°: 7km/h headwind & 7km/h crosswind from the left"
+        );
+        assert_eq!(
+            segment.as_string(&Units::metric(), &Locale::Pt),
+            "90°: 7km/h vento contra & 7km/h vento lateral da esquerda"
+        );
+    }
+
+    const GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+  <trk><trkseg>
+    <trkpt lat="45.0" lon="-75.0"><ele>70</ele></trkpt>
+    <trkpt lat="45.03" lon="-75.0"></trkpt>
+    <trkpt lat="45.06" lon="-75.0"></trkpt>
+    <trkpt lat="45.06" lon="-74.94"></trkpt>
+  </trkseg></trk>
+</gpx>"#;
+
+    const TCX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<TrainingCenterDatabase xmlns="http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2">
+  <Courses><Course><Track>
+    <Trackpoint><Position><LatitudeDegrees>45.0</LatitudeDegrees><LongitudeDegrees>-75.0</LongitudeDegrees></Position></Trackpoint>
+    <Trackpoint><Time>2021-05-20T08:00:00Z</Time></Trackpoint>
+    <Trackpoint><Position><LatitudeDegrees>45.1</LatitudeDegrees><LongitudeDegrees>-75.0</LongitudeDegrees></Position></Trackpoint>
+  </Track></Course></Courses>
+</TrainingCenterDatabase>"#;
+
+    #[test]
+    fn test_parse_gpx() {
+        let points = parse(GPX).unwrap();
+        assert_eq!(points.len(), 4);
+        assert_eq!(
+            points[3],
+            Point {
+                latitude: 45.06,
+                longitude: -74.94
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_tcx() {
+        let points = parse(TCX).unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1].latitude, 45.1);
+    }
+
+    #[test]
+    fn test_parse_invalid_course() {
+        assert!(parse("not xml").is_err());
+    }
+
+    #[test]
+    fn test_point_distance_and_bearing() {
+        let start = Point {
+            latitude: 45.0,
+            longitude: -75.0,
+        };
+        let north = Point {
+            latitude: 45.1,
+            longitude: -75.0,
+        };
+        assert_eq!(round(start.distance(&north), 1), 11.1);
+        assert_eq!(round(start.bearing(&north), 1), 0.0);
+        assert_eq!(round(north.bearing(&start), 1), 180.0);
+    }
+
+    #[test]
+    fn test_sample() {
+        let waypoints = sample(&parse(GPX).unwrap(), 5.0).unwrap();
+        let distances = waypoints.iter().map(|w| w.distance).collect::<Vec<f64>>();
+        let bearings = waypoints.iter().map(|w| w.bearing).collect::<Vec<f64>>();
+        assert_eq!(distances, vec![0.0, 6.7, 11.4]);
+        assert_eq!(bearings, vec![0.0, 90.0, 90.0]);
+    }
+
+    #[test]
+    fn test_sample_single_point() {
+        let points = parse(GPX).unwrap();
+        assert!(sample(&points[..1], 5.0).is_err());
+    }
+
+    #[test]
+    fn test_arrival() {
+        let start = NaiveDateTime::parse_from_str("2021-05-20 08:00", "%Y-%m-%d %H:%M").unwrap();
+        let waypoint = Waypoint {
+            latitude: 45.0,
+            longitude: -75.0,
+            distance: 21.1,
+            bearing: 0.0,
+        };
+        assert_eq!(
+            arrival(start, &waypoint, 12.0),
+            NaiveDateTime::parse_from_str("2021-05-20 09:45:30", "%Y-%m-%d %H:%M:%S").ok()
+        );
+        assert_eq!(arrival(start, &waypoint, 0.0), None);
+        assert_eq!(arrival(start, &waypoint, -5.0), None);
+    }
+
+    #[test]
+    fn test_checkpoint_as_string() {
+        let weather = Weather {
+            name: None,
+            location: "".to_string(),
+            units: Units::metric(),
+            icon: "☀️",
+            date: NaiveDateTime::parse_from_str("2021-05-20 10:00", "%Y-%m-%d %H:%M").unwrap(),
+            weather_code: 1,
+            description: "mainly clear",
+            probability_of_precipitation: 10,
+            temperature: 18.0,
+            feels_like: 18.0,
+            humidity: 60,
+            wind_speed: 10.0,
+            wind_direction: 0,
+            wind: Wind::new(0, 10.0),
+            precipitation: None,
+            wind_gusts: None,
+            uv_index: None,
+            cloud_cover: None,
+            dew_point: None,
+            visibility: None,
+            surface_pressure: None,
+            solar_radiation: None,
+            stress: crate::stress::Stress::new(18.0, 60.0, 10.0, None, &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
+        };
+        let waypoint = Waypoint {
+            latitude: 45.0,
+            longitude: -75.0,
+            distance: 21.1,
+            bearing: 0.0,
+        };
+        let arrival = NaiveDateTime::parse_from_str("2021-05-20 09:45", "%Y-%m-%d %H:%M").unwrap();
+        let checkpoint = Checkpoint::new(&waypoint, arrival, &weather);
+        assert_eq!(
+            checkpoint.as_string(&Units::metric(), &Locale::En),
+            "21.1km at 09:45 ☀️ 18°C, 10% chance of rain, 0°: 10km/h headwind & 0km/h crosswind from the right"
+        );
+    }
+}
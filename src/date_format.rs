@@ -2,7 +2,7 @@ use chrono::NaiveDateTime;
 use serde::{self, Deserialize, Deserializer, Serializer};
 
 pub const OPEN_METEO_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M";
-const WED_FILE_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+pub(crate) const WED_FILE_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
 pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
 where
@@ -19,3 +19,18 @@ where
     let s = format!("{}", date.format(WED_FILE_DATE_FORMAT));
     serializer.serialize_str(&s)
 }
+
+pub mod option {
+    use chrono::NaiveDateTime;
+    use serde::Serializer;
+
+    pub fn serialize<S>(date: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match date {
+            Some(date) => super::serialize(date, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
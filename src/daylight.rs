@@ -0,0 +1,189 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime};
+use serde::{self, Serialize};
+
+use crate::date_format;
+use crate::i18n::Locale;
+
+const SUNRISE_ALTITUDE: f64 = -0.833;
+const CIVIL_TWILIGHT_ALTITUDE: f64 = -6.0;
+const OBLIQUITY: f64 = 23.4397;
+const J2000: f64 = 2_451_545.0;
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2_440_587.5;
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Times of sunrise, sunset and civil twilight in local time, computed with the sunrise equation.
+/// They are missing when the sun does not cross the corresponding altitude (polar day or night).
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Daylight {
+    #[serde(with = "date_format::option")]
+    pub dawn: Option<NaiveDateTime>,
+    #[serde(with = "date_format::option")]
+    pub sunrise: Option<NaiveDateTime>,
+    #[serde(with = "date_format::option")]
+    pub sunset: Option<NaiveDateTime>,
+    #[serde(with = "date_format::option")]
+    pub dusk: Option<NaiveDateTime>,
+
+    /// Whether any part of the event is before sunrise or after sunset
+    pub dark: bool,
+
+    #[serde(skip)]
+    polar_night: bool,
+}
+
+/// Julian day of the solar transit, and the cosine of the hour angle of the sun at the given
+/// altitude (outside of `-1..=1` when the sun never gets there that day).
+fn hour_angle(date: NaiveDate, latitude: f64, longitude: f64, altitude: f64) -> (f64, f64) {
+    let days = (date - NaiveDate::default()).num_days() as f64;
+    let noon = UNIX_EPOCH_JULIAN_DAY + days + 0.5;
+    let mean = noon - J2000 + 0.0008 - longitude / 360.0;
+    let anomaly = (357.5291 + 0.985_600_28 * mean)
+        .rem_euclid(360.0)
+        .to_radians();
+    let center =
+        1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+    let ecliptic = (anomaly.to_degrees() + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit = J2000 + mean + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic).sin();
+    let declination = (ecliptic.sin() * OBLIQUITY.to_radians().sin()).asin();
+    let latitude = latitude.to_radians();
+    let cos = (altitude.to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+    (transit, cos)
+}
+
+fn local(julian_day: f64, utc_offset: i32) -> Option<NaiveDateTime> {
+    let seconds = ((julian_day - UNIX_EPOCH_JULIAN_DAY) * 86_400.0).round() as i64;
+    let utc = DateTime::from_timestamp(seconds, 0)?.naive_utc();
+    Some(utc + Duration::seconds(utc_offset.into()))
+}
+
+/// Times when the sun is at the given altitude, in the morning and in the evening.
+fn crossings(
+    date: NaiveDate,
+    latitude: f64,
+    longitude: f64,
+    utc_offset: i32,
+    altitude: f64,
+) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+    let (transit, cos) = hour_angle(date, latitude, longitude, altitude);
+    if !(-1.0..=1.0).contains(&cos) {
+        return (None, None);
+    }
+    let angle = cos.acos().to_degrees() / 360.0;
+    (
+        local(transit - angle, utc_offset),
+        local(transit + angle, utc_offset),
+    )
+}
+
+impl Daylight {
+    /// `utc_offset` in seconds, as the times are local to the event.
+    pub fn new(date: NaiveDate, latitude: f64, longitude: f64, utc_offset: i32) -> Self {
+        let (sunrise, sunset) = crossings(date, latitude, longitude, utc_offset, SUNRISE_ALTITUDE);
+        let (dawn, dusk) = crossings(
+            date,
+            latitude,
+            longitude,
+            utc_offset,
+            CIVIL_TWILIGHT_ALTITUDE,
+        );
+        let (_, cos) = hour_angle(date, latitude, longitude, SUNRISE_ALTITUDE);
+        Self {
+            dawn,
+            sunrise,
+            sunset,
+            dusk,
+            dark: false,
+            polar_night: cos > 1.0,
+        }
+    }
+
+    /// Flags the event as in the dark if it starts before sunrise or ends after sunset.
+    pub fn window(&mut self, start: NaiveDateTime, end: NaiveDateTime) {
+        self.dark = match (self.sunrise, self.sunset) {
+            (Some(sunrise), Some(sunset)) => start < sunrise || end > sunset,
+            _ => self.polar_night,
+        };
+    }
+
+    pub fn as_string(&self, locale: &Locale) -> String {
+        let time = |value: Option<NaiveDateTime>| match value {
+            Some(value) => value.format(TIME_FORMAT).to_string(),
+            None => "--:--".to_string(),
+        };
+        let mut output = format!(
+            "{} {} & {} {} ({} {}–{})",
+            locale.t("sunrise"),
+            time(self.sunrise),
+            locale.t("sunset"),
+            time(self.sunset),
+            locale.t("civil twilight"),
+            time(self.dawn),
+            time(self.dusk)
+        );
+        if self.dark {
+            output.push_str(&format!(" {}", locale.t("in the dark, bring lights")));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ottawa() -> Daylight {
+        let date = NaiveDate::from_ymd_opt(2021, 6, 21).unwrap();
+        Daylight::new(date, 45.42, -75.7, -4 * 3_600)
+    }
+
+    fn at(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("2021-06-21 {time}"), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_daylight_new() {
+        let daylight = ottawa();
+        let time = |value: Option<NaiveDateTime>| value.unwrap().format(TIME_FORMAT).to_string();
+        assert_eq!(time(daylight.dawn), "04:37");
+        assert_eq!(time(daylight.sunrise), "05:15");
+        assert_eq!(time(daylight.sunset), "20:55");
+        assert_eq!(time(daylight.dusk), "21:33");
+    }
+
+    #[test]
+    fn test_daylight_window() {
+        let mut daylight = ottawa();
+        daylight.window(at("07:00"), at("11:00"));
+        assert!(!daylight.dark);
+        daylight.window(at("05:00"), at("11:00"));
+        assert!(daylight.dark);
+        daylight.window(at("19:00"), at("21:00"));
+        assert!(daylight.dark);
+    }
+
+    #[test]
+    fn test_daylight_polar_night() {
+        let date = NaiveDate::from_ymd_opt(2021, 12, 21).unwrap();
+        let mut daylight = Daylight::new(date, 78.2, 15.6, 3_600);
+        assert_eq!(daylight.sunrise, None);
+        daylight.window(at("12:00"), at("13:00"));
+        assert!(daylight.dark);
+    }
+
+    #[test]
+    fn test_daylight_as_string() {
+        let mut daylight = ottawa();
+        daylight.window(at("05:00"), at("07:00"));
+        assert_eq!(
+            daylight.as_string(&Locale::En),
+            "sunrise 05:15 & sunset 20:55 (civil twilight 04:37–21:33) in the dark, bring lights"
+        );
+        assert_eq!(
+            daylight.as_string(&Locale::De),
+            "Sonnenaufgang 05:15 & Sonnenuntergang 20:55 (bürgerliche Dämmerung 04:37–21:33) im Dunkeln, Licht mitnehmen"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use serde::{self, Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::http::Client;
+use crate::open_meteo;
+use crate::units::{round, Units};
+use crate::Event;
+
+const API_URL: &str = "https://ensemble-api.open-meteo.com/v1/ensemble";
+
+/// GFS ensemble has 31 members and covers the 16 days of the forecast.
+const MODEL: &str = "gfs_seamless";
+
+/// Median and 10th–90th percentile range of the ensemble members.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Range {
+    pub median: f64,
+    pub p10: f64,
+    pub p90: f64,
+}
+
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = percent / 100.0 * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+}
+
+impl Range {
+    fn new(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        Some(Self {
+            median: percentile(&sorted, 50.0),
+            p10: percentile(&sorted, 10.0),
+            p90: percentile(&sorted, 90.0),
+        })
+    }
+
+    fn map(self, convert: impl Fn(f64) -> f64) -> Self {
+        Self {
+            median: convert(self.median),
+            p10: convert(self.p10),
+            p90: convert(self.p90),
+        }
+    }
+
+    /// The 10th–90th percentile range, e.g. `(14–21)`.
+    pub fn as_string(&self, decimals: i32) -> String {
+        format!(
+            "({}–{})",
+            round(self.p10, decimals),
+            round(self.p90, decimals)
+        )
+    }
+}
+
+/// Spread of the ensemble forecast for the event hour, as a measure of confidence.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Spread {
+    pub temperature: Option<Range>,
+    pub precipitation: Option<Range>,
+    pub wind_speed: Option<Range>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Hourly {
+    time: Vec<String>,
+
+    /// Each variable for the control run (e.g. `temperature_2m`) and for each member (e.g.
+    /// `temperature_2m_member01`)
+    #[serde(flatten)]
+    values: HashMap<String, Vec<Option<f64>>>,
+}
+
+impl Hourly {
+    fn range(&self, idx: usize, variable: &str) -> Option<Range> {
+        let member = format!("{variable}_member");
+        let values = self
+            .values
+            .iter()
+            .filter(|(name, _)| *name == variable || name.starts_with(&member))
+            .filter_map(|(_, values)| values.get(idx).copied().flatten())
+            .collect::<Vec<f64>>();
+        Range::new(&values)
+    }
+
+    /// Spread at the hour closest to the target.
+    fn at(&self, target: NaiveDateTime) -> Option<Spread> {
+        let idx = open_meteo::closest(&self.time, target)?;
+        Some(Spread {
+            temperature: self.range(idx, "temperature_2m"),
+            precipitation: self.range(idx, "precipitation"),
+            wind_speed: self.range(idx, "wind_speed_10m"),
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Response {
+    hourly: Hourly,
+}
+
+/// Fetches the ensemble forecast at the event's location for the event hour.
+pub async fn for_event(event: &Event, units: &Units, client: &Client) -> Result<Option<Spread>> {
+    let date = event.when.date().to_string();
+    let mut query = open_meteo::locations(&[(event.latitude, event.longitude)]).to_vec();
+    query.extend([
+        ("start_date", date.clone()),
+        ("end_date", date),
+        ("models", MODEL.to_string()),
+        ("temperature_unit", units.temperature()),
+        ("wind_speed_unit", units.speed()),
+        ("precipitation_unit", units.precipitation()),
+        ("timezone", "auto".to_string()),
+        (
+            "hourly",
+            "temperature_2m,precipitation,wind_speed_10m".to_string(),
+        ),
+    ]);
+    let (responses, _) = open_meteo::fetch::<Response>(API_URL, &query, 1, client).await?;
+    Ok(responses[0].hourly.at(event.when).map(|spread| Spread {
+        temperature: spread
+            .temperature
+            .map(|range| range.map(|v| units.from_response_temperature(v))),
+        precipitation: spread.precipitation,
+        wind_speed: spread
+            .wind_speed
+            .map(|range| range.map(|v| units.from_response_speed(v))),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOURLY: &str = r#"{
+        "time": ["2021-05-20T08:00", "2021-05-20T09:00"],
+        "temperature_2m": [17.0, 18.0],
+        "temperature_2m_member01": [14.0, 15.0],
+        "temperature_2m_member02": [21.0, 22.0],
+        "temperature_2m_member03": [16.0, 17.0],
+        "temperature_2m_member04": [18.0, null],
+        "precipitation": [0.0, 0.0],
+        "precipitation_member01": [1.0, 2.0],
+        "wind_speed_10m": [10.0, 12.0]
+    }"#;
+
+    #[test]
+    fn test_range_new() {
+        let range = Range::new(&[14.0, 21.0, 16.0, 18.0, 17.0]).unwrap();
+        assert_eq!(range.median, 17.0);
+        assert_eq!(round(range.p10, 1), 14.8);
+        assert_eq!(round(range.p90, 1), 19.8);
+        assert_eq!(Range::new(&[]), None);
+    }
+
+    #[test]
+    fn test_hourly_at() {
+        let hourly: Hourly = serde_json::from_str(HOURLY).unwrap();
+        let when = NaiveDateTime::parse_from_str("2021-05-20 08:10", "%Y-%m-%d %H:%M").unwrap();
+        let spread = hourly.at(when).unwrap();
+        assert_eq!(spread.temperature.unwrap().median, 17.0);
+        assert_eq!(spread.precipitation.unwrap().median, 0.5);
+        assert_eq!(spread.wind_speed.unwrap().median, 10.0);
+
+        let when = NaiveDateTime::parse_from_str("2021-05-20 09:00", "%Y-%m-%d %H:%M").unwrap();
+        let spread = hourly.at(when).unwrap();
+        assert_eq!(spread.temperature.unwrap().median, 17.5);
+    }
+
+    #[test]
+    fn test_range_as_string() {
+        let range = Range::new(&[14.0, 21.0, 16.0, 18.0, 17.0]).unwrap();
+        assert_eq!(range.as_string(0), "(15–20)");
+        assert_eq!(range.as_string(1), "(14.8–19.8)");
+    }
+}
@@ -0,0 +1,173 @@
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The location could not be converted to latitude and longitude
+    #[error("{0}")]
+    Geocoding(String),
+
+    /// A weather or geocoding provider responded with an unsuccessful HTTP status
+    #[error("HTTP request to {url} returned {status}: {body}")]
+    Http {
+        url: String,
+        status: u16,
+        body: String,
+    },
+
+    /// A request could not be completed (e.g. timeout, DNS or connection errors)
+    #[error("HTTP request to {url} failed: {message}")]
+    Network { url: String, message: String },
+
+    /// Unexpected input or response contents (e.g. a malformed date or JSON body)
+    #[error("{0}")]
+    Parse(String),
+
+    /// Reading or writing saved events, settings or cache
+    #[error("{0}")]
+    Storage(String),
+
+    /// Valid input that `wed` cannot work with (e.g. an event beyond the forecast range)
+    #[error("{0}")]
+    Validation(String),
+}
+
+impl Error {
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, Error::Http { status: 429, .. })
+    }
+
+    /// Exit code for the command-line interface, distinct for each kind of error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Geocoding(_) => 3,
+            Error::Http { status: 429, .. } => 8,
+            Error::Http { .. } => 4,
+            Error::Network { .. } => 9,
+            Error::Parse(_) => 5,
+            Error::Storage(_) => 6,
+            Error::Validation(_) => 7,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        let url = e.url().map(|url| url.to_string()).unwrap_or_default();
+        if e.is_decode() {
+            return Error::Parse(format!("Failed to parse response from {url}: {e}"));
+        }
+        Error::Network {
+            url,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Storage(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        if e.is_io() {
+            return Error::Storage(e.to_string());
+        }
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<serde_yaml_ng::Error> for Error {
+    fn from(e: serde_yaml_ng::Error) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<minijinja::Error> for Error {
+    fn from(e: minijinja::Error) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<chrono::ParseError> for Error {
+    fn from(e: chrono::ParseError) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<std::num::ParseFloatError> for Error {
+    fn from(e: std::num::ParseFloatError) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<reqwest::header::ToStrError> for Error {
+    fn from(e: reqwest::header::ToStrError) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_error_is_rate_limited() {
+        let error = Error::Http {
+            url: "https://nominatim.openstreetmap.org/".to_string(),
+            status: 429,
+            body: "".to_string(),
+        };
+        assert!(error.is_rate_limited());
+        assert!(!Error::Parse("".to_string()).is_rate_limited());
+    }
+
+    #[test]
+    fn test_error_exit_codes_are_distinct() {
+        let errors = [
+            Error::Geocoding("".to_string()),
+            Error::Http {
+                url: "".to_string(),
+                status: 500,
+                body: "".to_string(),
+            },
+            Error::Http {
+                url: "".to_string(),
+                status: 429,
+                body: "".to_string(),
+            },
+            Error::Network {
+                url: "".to_string(),
+                message: "".to_string(),
+            },
+            Error::Parse("".to_string()),
+            Error::Storage("".to_string()),
+            Error::Validation("".to_string()),
+        ];
+        let codes = errors
+            .iter()
+            .map(|e| e.exit_code())
+            .collect::<HashSet<i32>>();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0));
+        assert!(!codes.contains(&1));
+    }
+
+    #[test]
+    fn test_error_from_io() {
+        let error: Error = std::io::Error::new(std::io::ErrorKind::NotFound, "oops").into();
+        assert!(matches!(error, Error::Storage(_)));
+        assert_eq!(error.exit_code(), 6);
+    }
+}
@@ -0,0 +1,464 @@
+use std::{fs::File, io::BufReader, path::PathBuf};
+
+use clap::ValueEnum;
+use serde::{self, Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::i18n::Locale;
+use crate::weather::Weather;
+
+const FILE_NAME: &str = ".wed.gear.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sport {
+    Run,
+    Bike,
+    Swim,
+}
+
+/// Conditions in Celsius, km/h and mm regardless of the units of the forecast. A rule applies
+/// when all the conditions that are set are met (limits are exclusive).
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Conditions {
+    pub temperature_below: Option<f64>,
+    pub temperature_above: Option<f64>,
+    pub feels_like_below: Option<f64>,
+    pub feels_like_above: Option<f64>,
+    pub rain_probability_above: Option<f64>,
+    pub precipitation_above: Option<f64>,
+    pub wind_speed_above: Option<f64>,
+    pub uv_index_above: Option<f64>,
+}
+
+impl Conditions {
+    fn matches(&self, weather: &Weather) -> bool {
+        let units = &weather.units;
+        let temperature = units.to_celsius(weather.temperature);
+        let feels_like = units.to_celsius(weather.feels_like);
+        let below = |limit: Option<f64>, value: f64| limit.is_none_or(|limit| value < limit);
+        let above = |limit: Option<f64>, value: f64| limit.is_none_or(|limit| value > limit);
+        below(self.temperature_below, temperature)
+            && above(self.temperature_above, temperature)
+            && below(self.feels_like_below, feels_like)
+            && above(self.feels_like_above, feels_like)
+            && above(
+                self.rain_probability_above,
+                weather.probability_of_precipitation.into(),
+            )
+            && above(
+                self.precipitation_above,
+                units.to_mm(weather.precipitation.unwrap_or(0.0)),
+            )
+            && above(self.wind_speed_above, units.to_kmh(weather.wind_speed))
+            && above(self.uv_index_above, weather.uv_index.unwrap_or(0.0))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub sports: Vec<Sport>,
+    pub item: String,
+    pub when: Conditions,
+}
+
+impl Rule {
+    fn new(sports: &[Sport], item: &str, when: Conditions) -> Self {
+        Self {
+            sports: sports.to_vec(),
+            item: item.to_string(),
+            when,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    pub sport: Sport,
+    pub item: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Rules(pub Vec<Rule>);
+
+/// Rules from the file: a list merged into the built-in ones, or an object with `builtin` set to
+/// `false` to replace them altogether.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Custom {
+    Merge(Vec<Rule>),
+    Replace { builtin: bool, rules: Vec<Rule> },
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        use Sport::*;
+        let all = [Run, Bike, Swim];
+        Self(vec![
+            Rule::new(
+                &all,
+                "sunscreen",
+                Conditions {
+                    uv_index_above: Some(2.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Run],
+                "cap",
+                Conditions {
+                    uv_index_above: Some(5.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Run],
+                "tights",
+                Conditions {
+                    feels_like_below: Some(8.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Run],
+                "gloves",
+                Conditions {
+                    feels_like_below: Some(3.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Run, Bike],
+                "rain jacket",
+                Conditions {
+                    rain_probability_above: Some(50.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Run, Bike],
+                "wind vest",
+                Conditions {
+                    wind_speed_above: Some(25.0),
+                    temperature_below: Some(18.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Bike],
+                "arm warmers",
+                Conditions {
+                    temperature_below: Some(16.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Bike],
+                "knee warmers",
+                Conditions {
+                    temperature_below: Some(12.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Bike],
+                "full-finger gloves",
+                Conditions {
+                    feels_like_below: Some(8.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Bike],
+                "overshoes",
+                Conditions {
+                    feels_like_below: Some(5.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Bike],
+                "overshoes",
+                Conditions {
+                    precipitation_above: Some(1.0),
+                    ..Default::default()
+                },
+            ),
+            // the forecast has no water temperature, so these rely on the air temperature
+            Rule::new(
+                &[Swim],
+                "wetsuit",
+                Conditions {
+                    temperature_below: Some(22.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Swim],
+                "neoprene cap",
+                Conditions {
+                    temperature_below: Some(15.0),
+                    ..Default::default()
+                },
+            ),
+            Rule::new(
+                &[Swim],
+                "tinted goggles",
+                Conditions {
+                    uv_index_above: Some(5.0),
+                    ..Default::default()
+                },
+            ),
+        ])
+    }
+}
+
+fn default_rules_path() -> Result<PathBuf> {
+    let mut path =
+        home::home_dir().ok_or(Error::Storage("Couldn't find home directory".to_string()))?;
+    path.push(FILE_NAME);
+    Ok(path)
+}
+
+impl Rules {
+    /// Rules from the file replace the built-in ones for the same item and sport, and the other
+    /// ones are added to them (unless the file opts out of the built-in rules).
+    pub fn from_file_path(path: &PathBuf) -> Result<Self> {
+        let rules = Self::default();
+        if !path.exists() {
+            return Ok(rules);
+        }
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        Ok(match serde_json::from_reader(reader)? {
+            Custom::Merge(custom) => rules.merge(custom),
+            Custom::Replace {
+                builtin: true,
+                rules: custom,
+            } => rules.merge(custom),
+            Custom::Replace { rules: custom, .. } => Self(custom),
+        })
+    }
+
+    fn merge(mut self, custom: Vec<Rule>) -> Self {
+        for rule in &mut self.0 {
+            rule.sports.retain(|sport| {
+                !custom
+                    .iter()
+                    .any(|other| other.item == rule.item && other.sports.contains(sport))
+            });
+        }
+        self.0.retain(|rule| !rule.sports.is_empty());
+        self.0.extend(custom);
+        self
+    }
+
+    pub fn from_file() -> Result<Self> {
+        Self::from_file_path(&default_rules_path()?)
+    }
+
+    /// Items for each sport, in the order of the sports and then of the rules, without repetition.
+    pub fn recommend(&self, weather: &Weather, sports: &[Sport]) -> Vec<Recommendation> {
+        let mut recommendations: Vec<Recommendation> = vec![];
+        for sport in sports {
+            for rule in &self.0 {
+                if !rule.sports.contains(sport) || !rule.when.matches(weather) {
+                    continue;
+                }
+                let recommendation = Recommendation {
+                    sport: *sport,
+                    item: rule.item.clone(),
+                };
+                if !recommendations.contains(&recommendation) {
+                    recommendations.push(recommendation);
+                }
+            }
+        }
+        recommendations
+    }
+}
+
+/// Groups the items by sport, e.g. `bike: arm warmers, rain jacket; run: rain jacket`.
+pub fn as_line(recommendations: &[Recommendation], locale: &Locale) -> String {
+    let mut sports: Vec<Sport> = vec![];
+    for recommendation in recommendations {
+        if !sports.contains(&recommendation.sport) {
+            sports.push(recommendation.sport);
+        }
+    }
+    sports
+        .iter()
+        .map(|sport| {
+            let items = recommendations
+                .iter()
+                .filter(|r| r.sport == *sport)
+                .map(|r| locale.t(&r.item))
+                .collect::<Vec<&str>>()
+                .join(", ");
+            let name = sport
+                .to_possible_value()
+                .map(|value| value.get_name().to_string())
+                .unwrap_or_default();
+            format!("{}: {items}", locale.t(&name))
+        })
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stress::Stress;
+    use crate::units::Units;
+    use crate::wind::Wind;
+    use chrono::NaiveDateTime;
+    use std::fs;
+    use tempdir::TempDir;
+
+    fn weather<'a>(temperature: f64, rain: i8, uv_index: f64, units: Units) -> Weather<'a> {
+        Weather {
+            name: None,
+            location: "Location".to_string(),
+            units: units.clone(),
+            icon: "☀️",
+            date: NaiveDateTime::parse_from_str("2021-05-20 07:00", "%Y-%m-%d %H:%M").unwrap(),
+            weather_code: 1,
+            description: "mainly clear",
+            probability_of_precipitation: rain,
+            temperature,
+            feels_like: temperature,
+            humidity: 60,
+            wind_speed: 5.0,
+            wind_direction: 180,
+            wind: Wind::new(180, 5.0),
+            precipitation: None,
+            wind_gusts: None,
+            uv_index: Some(uv_index),
+            cloud_cover: None,
+            dew_point: None,
+            visibility: None,
+            surface_pressure: None,
+            solar_radiation: None,
+            stress: Stress::new(temperature, 60.0, 5.0, None, &units),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
+        }
+    }
+
+    fn items(recommendations: &[Recommendation]) -> Vec<&str> {
+        recommendations.iter().map(|r| r.item.as_str()).collect()
+    }
+
+    #[test]
+    fn test_recommend_cool_and_wet_ride() {
+        let weather = weather(11.0, 70, 1.0, Units::metric());
+        let recommendations = Rules::default().recommend(&weather, &[Sport::Bike]);
+        assert_eq!(
+            items(&recommendations),
+            vec!["rain jacket", "arm warmers", "knee warmers"]
+        );
+    }
+
+    #[test]
+    fn test_recommend_sunny_run_in_fahrenheit() {
+        let weather = weather(77.0, 0, 7.0, Units::imperial());
+        let recommendations = Rules::default().recommend(&weather, &[Sport::Run]);
+        assert_eq!(items(&recommendations), vec!["sunscreen", "cap"]);
+    }
+
+    #[test]
+    fn test_recommend_without_sports() {
+        let weather = weather(5.0, 90, 8.0, Units::metric());
+        assert!(Rules::default().recommend(&weather, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_as_line() {
+        let weather = weather(20.0, 0, 4.0, Units::metric());
+        let recommendations = Rules::default().recommend(&weather, &[Sport::Swim, Sport::Run]);
+        assert_eq!(
+            as_line(&recommendations, &Locale::En),
+            "swim: sunscreen, wetsuit; run: sunscreen"
+        );
+        assert_eq!(
+            as_line(&recommendations, &Locale::Fr),
+            "natation: crème solaire, combinaison; course: crème solaire"
+        );
+    }
+
+    #[test]
+    fn test_rules_from_file_path() {
+        let tmp = TempDir::new("wed-test-gear").unwrap();
+        let path = tmp.path().join(FILE_NAME);
+        fs::write(
+            &path,
+            r#"[{"sports": ["run"], "item": "visor", "when": {"temperature_above": 15}}]"#,
+        )
+        .unwrap();
+        let rules = Rules::from_file_path(&path).unwrap();
+        tmp.close().unwrap();
+
+        let weather = weather(20.0, 0, 4.0, Units::metric());
+        assert_eq!(
+            items(&rules.recommend(&weather, &[Sport::Run])),
+            vec!["sunscreen", "visor"]
+        );
+    }
+
+    #[test]
+    fn test_rules_from_file_path_override_builtin_item() {
+        let tmp = TempDir::new("wed-test-gear").unwrap();
+        let path = tmp.path().join(FILE_NAME);
+        fs::write(
+            &path,
+            r#"[{"sports": ["run"], "item": "sunscreen", "when": {"uv_index_above": 6}}]"#,
+        )
+        .unwrap();
+        let rules = Rules::from_file_path(&path).unwrap();
+        tmp.close().unwrap();
+
+        let weather = weather(20.0, 0, 4.0, Units::metric());
+        assert!(rules.recommend(&weather, &[Sport::Run]).is_empty());
+        assert_eq!(
+            items(&rules.recommend(&weather, &[Sport::Bike])),
+            vec!["sunscreen"]
+        );
+    }
+
+    #[test]
+    fn test_rules_from_file_path_replace_builtin() {
+        let tmp = TempDir::new("wed-test-gear").unwrap();
+        let path = tmp.path().join(FILE_NAME);
+        fs::write(
+            &path,
+            r#"{"builtin": false, "rules": [{"sports": ["bike"], "item": "visor", "when": {}}]}"#,
+        )
+        .unwrap();
+        let rules = Rules::from_file_path(&path).unwrap();
+        tmp.close().unwrap();
+
+        let weather = weather(20.0, 0, 4.0, Units::metric());
+        assert_eq!(rules.0.len(), 1);
+        assert_eq!(
+            items(&rules.recommend(&weather, &[Sport::Bike])),
+            vec!["visor"]
+        );
+    }
+
+    #[test]
+    fn test_rules_from_missing_file_path() {
+        let tmp = TempDir::new("wed-test-gear").unwrap();
+        let rules = Rules::from_file_path(&tmp.path().join(FILE_NAME)).unwrap();
+        tmp.close().unwrap();
+        assert_eq!(rules, Rules::default());
+    }
+}
@@ -1,10 +1,16 @@
-use anyhow::{anyhow, Result};
-use reqwest::Client;
+use std::time::Duration;
+
+use reqwest::Url;
 use serde::Deserialize;
 
+use crate::error::{Error, Result};
+use crate::http::Client;
 use crate::persistence::SavedEvents;
+use crate::throttle::Throttle;
 
-const NOMINATIM_URL: &str = "https://nominatim.openstreetmap.org/search.php?format=jsonv2&q=";
+const NOMINATIM_URL: &str = "https://nominatim.openstreetmap.org/search.php";
+const NOMINATIM_THROTTLE_FILE_NAME: &str = ".wed.nominatim";
+const NOMINATIM_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Deserialize, Debug)]
 struct Location {
@@ -23,38 +29,42 @@ fn coordinates_from_saved_events(query: &str) -> Option<(f64, f64)> {
     None
 }
 
-pub async fn coordinates(query: &str) -> Result<(f64, f64)> {
+fn search_url(query: &str) -> Result<Url> {
+    Url::parse_with_params(NOMINATIM_URL, [("format", "jsonv2"), ("q", query)])
+        .map_err(|e| Error::Geocoding(format!("Invalid location {query}: {e}")))
+}
+
+pub async fn coordinates(query: &str, client: &Client) -> Result<(f64, f64)> {
     if let Some(coordinates) = coordinates_from_saved_events(query) {
         return Ok(coordinates);
     }
-    let url = format!("{NOMINATIM_URL}{query}");
-    let user_agent = format!(
-        "{}/{} ({})",
-        env!("CARGO_PKG_NAME"),
-        env!("CARGO_PKG_VERSION"),
-        env!("CARGO_PKG_REPOSITORY"),
-    );
-
-    let resp = Client::new()
-        .get(&url)
-        .header("User-Agent", user_agent)
-        .send()
-        .await?;
-    if !resp.status().is_success() {
-        return Err(anyhow!(
-            "HTTP request to {} returned {}: {}",
-            &url,
-            resp.status(),
-            resp.text().await?
-        ));
-    }
+    let url = search_url(query)?;
+    let throttle = Throttle::in_home_dir(NOMINATIM_THROTTLE_FILE_NAME, NOMINATIM_INTERVAL)?;
+    let resp = client.get_throttled(&url, Some(&throttle)).await?;
 
     let results: Vec<Location> = resp.json().await?;
     if results.is_empty() {
-        return Err(anyhow!("No latitude/longitude found for {}", query));
+        return Err(Error::Geocoding(format!(
+            "No latitude/longitude found for {}",
+            query
+        )));
     }
     Ok((
         results[0].lat.parse::<f64>()?,
         results[0].lon.parse::<f64>()?,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_url_encodes_the_query() {
+        let url = search_url("Rock & Roll Hall, Cleveland#1").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://nominatim.openstreetmap.org/search.php?format=jsonv2&q=Rock+%26+Roll+Hall%2C+Cleveland%231"
+        );
+    }
+}
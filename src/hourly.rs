@@ -0,0 +1,249 @@
+use chrono::NaiveDateTime;
+
+use crate::error::Result;
+use crate::output::{self, Options};
+use crate::weather::Weather;
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+const BOLD: &str = "1";
+const REVERSE: &str = "7";
+const RED: &str = "31";
+const BLUE: &str = "34";
+const CYAN: &str = "36";
+const HIGHLIGHT: &str = "1;33";
+
+/// One bar per value, scaled between the lowest and the highest of them.
+pub fn sparkline(values: &[f64]) -> Vec<char> {
+    let min = values.iter().copied().fold(f64::MAX, f64::min);
+    let max = values.iter().copied().fold(f64::MIN, f64::max);
+    values
+        .iter()
+        .map(|value| {
+            if (max - min).abs() < f64::EPSILON {
+                return BARS[BARS.len() / 2];
+            }
+            let idx = ((value - min) / (max - min) * (BARS.len() - 1) as f64).round() as usize;
+            BARS[idx]
+        })
+        .collect()
+}
+
+/// Wraps the text in an ANSI escape sequence, when colours are enabled.
+fn paint(text: &str, code: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Index of the hour closest to the event.
+fn event_hour(series: &[Weather], when: NaiveDateTime) -> Option<usize> {
+    series
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, weather)| (when - weather.date).num_minutes().abs())
+        .map(|(idx, _)| idx)
+}
+
+/// Aligned rows for each hour followed by sparklines for temperature, rain probability and wind,
+/// highlighting the event hour (with a marker, and in bold when colours are enabled).
+pub fn as_string(
+    series: &[Weather],
+    when: NaiveDateTime,
+    color: bool,
+    options: &Options,
+) -> Result<String> {
+    let (Some(first), Some(highlight)) = (series.first(), event_hour(series, when)) else {
+        return Ok("".to_string());
+    };
+    let notification = first.as_notification(options)?;
+    let (temperature, speed) = (first.units.temperature.symbol(), first.units.speed.symbol());
+
+    let locale = &options.locale;
+    let mut rows = vec![[
+        "".to_string(),
+        locale.t("time").to_string(),
+        "".to_string(),
+        locale.t("temperature").to_string(),
+        locale.t("rain").to_string(),
+        locale.t("wind").to_string(),
+    ]];
+    for (idx, weather) in series.iter().enumerate() {
+        rows.push([
+            if idx == highlight { "▶" } else { "" }.to_string(),
+            weather.date.format("%H:%M").to_string(),
+            weather.icon.to_string(),
+            format!("{}{}", weather.temperature.round(), temperature),
+            format!("{}%", weather.probability_of_precipitation),
+            format!(
+                "{}{} {}",
+                weather.wind_speed.round(),
+                speed,
+                options.wind.as_string(&weather.wind, locale)
+            ),
+        ]);
+    }
+    let mut lines = vec![format!("{} {}", notification.title, notification.subtitle)
+        .trim()
+        .to_string()];
+    for (idx, line) in output::align(&rows).into_iter().enumerate() {
+        lines.push(match idx {
+            0 => paint(&line, BOLD, color),
+            idx if idx == highlight + 1 => paint(&line, REVERSE, color),
+            _ => line,
+        });
+    }
+
+    let charts = [
+        (
+            locale.t("temperature"),
+            series.iter().map(|w| w.temperature).collect::<Vec<f64>>(),
+            temperature,
+            RED,
+        ),
+        (
+            locale.t("rain"),
+            series
+                .iter()
+                .map(|w| w.probability_of_precipitation.into())
+                .collect(),
+            "%",
+            BLUE,
+        ),
+        (
+            locale.t("wind"),
+            series.iter().map(|w| w.wind_speed).collect(),
+            speed,
+            CYAN,
+        ),
+    ];
+    let width = charts
+        .iter()
+        .map(|(label, ..)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    lines.push("".to_string());
+    for (label, values, unit, code) in &charts {
+        let bars = sparkline(values)
+            .iter()
+            .enumerate()
+            .map(|(idx, bar)| {
+                let code = if idx == highlight { HIGHLIGHT } else { code };
+                paint(&bar.to_string(), code, color)
+            })
+            .collect::<String>();
+        let min = values.iter().copied().fold(f64::MAX, f64::min);
+        let max = values.iter().copied().fold(f64::MIN, f64::max);
+        lines.push(format!(
+            "{label:width$}  {bars}  {}–{}{}",
+            min.round(),
+            max.round(),
+            unit
+        ));
+    }
+    if !color {
+        lines.push(format!(
+            "{}  {}▲ {}",
+            " ".repeat(width),
+            " ".repeat(highlight),
+            series[highlight].date.format("%H:%M")
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stress::Stress;
+    use crate::units::Units;
+    use crate::wind::Wind;
+
+    fn weather<'a>(hour: u32, temperature: f64, rain: i8, wind_speed: f64) -> Weather<'a> {
+        Weather {
+            name: None,
+            location: "Ottawa, CA".to_string(),
+            units: Units::metric(),
+            icon: "☀️",
+            date: NaiveDateTime::parse_from_str(
+                &format!("2021-05-20 {hour:02}:00"),
+                "%Y-%m-%d %H:%M",
+            )
+            .unwrap(),
+            weather_code: 1,
+            description: "mainly clear",
+            probability_of_precipitation: rain,
+            temperature,
+            feels_like: temperature,
+            humidity: 60,
+            wind_speed,
+            wind_direction: 180,
+            wind: Wind::new(180, wind_speed),
+            precipitation: None,
+            wind_gusts: None,
+            uv_index: None,
+            cloud_cover: None,
+            dew_point: None,
+            visibility: None,
+            surface_pressure: None,
+            solar_radiation: None,
+            stress: Stress::new(temperature, 60.0, wind_speed, None, &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
+        }
+    }
+
+    fn series<'a>() -> Vec<Weather<'a>> {
+        vec![
+            weather(7, 12.0, 0, 5.0),
+            weather(8, 15.0, 20, 10.0),
+            weather(9, 19.0, 60, 12.0),
+        ]
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0]), vec!['▁', '▅', '█']);
+        assert_eq!(sparkline(&[3.0, 3.0]), vec!['▅', '▅']);
+        assert!(sparkline(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_as_string() {
+        let when = NaiveDateTime::parse_from_str("2021-05-20 08:10", "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(
+            as_string(&series(), when, false, &Options::default()).unwrap(),
+            [
+                "🌐 Ottawa, CA",
+                "   time       temperature  rain  wind",
+                "   07:00  ☀️  12°C         0%    5km/h S",
+                "▶  08:00  ☀️  15°C         20%   10km/h S",
+                "   09:00  ☀️  19°C         60%   12km/h S",
+                "",
+                "temperature  ▁▄█  12–19°C",
+                "rain         ▁▃█  0–60%",
+                "wind         ▁▆█  5–12km/h",
+                "              ▲ 08:00",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_as_string_with_colors() {
+        let when = NaiveDateTime::parse_from_str("2021-05-20 09:00", "%Y-%m-%d %H:%M").unwrap();
+        let output = as_string(&series(), when, true, &Options::default()).unwrap();
+        assert!(output.contains("\x1b[7m▶  09:00"));
+        assert!(output.contains("\x1b[1;33m█\x1b[0m"));
+        assert!(!output.contains('▲'));
+    }
+}
@@ -0,0 +1,258 @@
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER, USER_AGENT};
+use reqwest::{Proxy, Response, StatusCode, Url};
+
+use crate::cache::Cache;
+use crate::config::{Config, HttpConfig};
+use crate::error::{Error, Result};
+use crate::throttle::Throttle;
+
+/// Longest a `Retry-After` header can make `wed` wait before retrying.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// HTTP client shared by all the requests `wed` makes to Nominatim and Open-Meteo. It sets the
+/// User-Agent, retries with exponential backoff on server and network errors, and caches
+/// forecasts on disk.
+#[derive(Clone, Debug)]
+pub struct Client {
+    inner: reqwest::Client,
+    user_agent: String,
+    http: HttpConfig,
+    cache: Option<Cache>,
+    offline: bool,
+}
+
+impl Client {
+    pub fn new(config: &Config) -> Result<Self> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(config.http.connect_timeout())
+            .read_timeout(config.http.read_timeout());
+        if let Some(proxy) = &config.http.proxy {
+            let proxy = Proxy::all(proxy)
+                .map_err(|e| Error::Validation(format!("Invalid proxy {proxy}: {e}")))?;
+            builder = builder.proxy(proxy);
+        }
+        let cache = Cache::in_home_dir(config.cache.max_age())?;
+        let inner = builder
+            .build()
+            .map_err(|e| Error::Validation(format!("Failed to build HTTP client: {e}")))?;
+        Ok(Self::from_reqwest(inner, config).with_cache(cache))
+    }
+
+    /// Wraps a client built by the caller (e.g. with custom TLS settings); timeouts and proxy
+    /// from the configuration are ignored, since they belong to the underlying client.
+    pub fn from_reqwest(inner: reqwest::Client, config: &Config) -> Self {
+        Self {
+            inner,
+            user_agent: config.user_agent(),
+            http: config.http.clone(),
+            cache: None,
+            offline: false,
+        }
+    }
+
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// In offline mode cached responses are used regardless of their age, and any request fails
+    /// right away (locations only resolve from saved events).
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub(crate) async fn get_throttled(
+        &self,
+        url: &Url,
+        throttle: Option<&Throttle>,
+    ) -> Result<Response> {
+        if self.offline {
+            return Err(Error::Validation(format!(
+                "Cannot request {} in offline mode",
+                url
+            )));
+        }
+        let mut attempt = 0;
+        loop {
+            if let Some(throttle) = throttle {
+                throttle.wait().await?;
+            }
+            let result = self
+                .inner
+                .get(url.clone())
+                .header(USER_AGENT, &self.user_agent)
+                .send()
+                .await;
+            let retry = match &result {
+                Ok(resp) if resp.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    Some(retry_after(resp.headers()).unwrap_or(self.http.backoff(attempt)))
+                }
+                Ok(resp) if resp.status().is_server_error() => Some(self.http.backoff(attempt)),
+                Ok(_) => None,
+                Err(e) if e.is_timeout() || e.is_connect() || e.is_request() => {
+                    Some(self.http.backoff(attempt))
+                }
+                Err(_) => None,
+            };
+            match retry {
+                Some(delay) if attempt < self.http.retries => {
+                    attempt += 1;
+                    tokio::time::sleep(delay).await;
+                }
+                _ => return check(url, result?).await,
+            }
+        }
+    }
+
+    pub async fn get(&self, url: &Url) -> Result<Response> {
+        self.get_throttled(url, None).await
+    }
+
+    /// Returns the response body, and the time it was fetched when it comes from a cached
+    /// response that is not fresh anymore (offline mode, or a failed request).
+    pub(crate) async fn get_cached(&self, url: &Url) -> Result<(String, Option<NaiveDateTime>)> {
+        let Some(cache) = &self.cache else {
+            return Ok((self.get(url).await?.text().await?, None));
+        };
+        if self.offline {
+            return match cache.get(url.as_str()) {
+                Some(entry) => Ok((entry.body, Some(entry.fetched_at))),
+                None => Err(Error::Validation(format!(
+                    "No cached response for {} in offline mode",
+                    url
+                ))),
+            };
+        }
+        if let Some(entry) = cache.get_fresh(url.as_str()) {
+            return Ok((entry.body, None));
+        }
+        let body = match self.get(url).await {
+            Ok(resp) => resp.text().await?,
+            Err(e) => {
+                return cache
+                    .get(url.as_str())
+                    .map(|entry| (entry.body, Some(entry.fetched_at)))
+                    .ok_or(e)
+            }
+        };
+        // failing to write to the cache should not prevent showing the forecast
+        let _ = cache.set(url.as_str(), &body);
+        Ok((body, None))
+    }
+}
+
+async fn check(url: &Url, resp: Response) -> Result<Response> {
+    if !resp.status().is_success() {
+        return Err(Error::Http {
+            url: url.to_string(),
+            status: resp.status().as_u16(),
+            body: resp.text().await?,
+        });
+    }
+    Ok(resp)
+}
+
+/// Delay asked by the server, capped at `MAX_RETRY_AFTER`.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    let delay = match value.parse::<u64>() {
+        Ok(seconds) => Duration::from_secs(seconds),
+        Err(_) => {
+            let when = DateTime::parse_from_rfc2822(value).ok()?;
+            (when.with_timezone(&Utc) - Utc::now()).to_std().ok()?
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_client_new_with_proxy() {
+        let config = Config {
+            http: HttpConfig {
+                proxy: Some("http://localhost:8080".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(Client::new(&config).is_ok());
+    }
+
+    #[test]
+    fn test_client_new_with_invalid_proxy() {
+        let config = Config {
+            http: HttpConfig {
+                proxy: Some("not a proxy".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(Client::new(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_offline_with_cached_response() {
+        let tmp = TempDir::new("wed-test-http").unwrap();
+        let cache = Cache::new(tmp.path().to_path_buf(), chrono::Duration::zero());
+        let url = Url::parse("https://api.open-meteo.com/v1/forecast").unwrap();
+        cache.set(url.as_str(), "{}").unwrap();
+        let client = Client::from_reqwest(reqwest::Client::new(), &Config::default())
+            .with_cache(cache)
+            .offline(true);
+        let (body, as_of) = client.get_cached(&url).await.unwrap();
+        tmp.close().unwrap();
+
+        assert_eq!(body, "{}");
+        assert!(as_of.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_client_offline_without_cached_response() {
+        let tmp = TempDir::new("wed-test-http").unwrap();
+        let cache = Cache::new(tmp.path().to_path_buf(), chrono::Duration::zero());
+        let url = Url::parse("https://api.open-meteo.com/v1/forecast").unwrap();
+        let client = Client::from_reqwest(reqwest::Client::new(), &Config::default())
+            .with_cache(cache)
+            .offline(true);
+        let result = client.get_cached(&url).await;
+        tmp.close().unwrap();
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_offline_does_not_request() {
+        let url = Url::parse("https://nominatim.openstreetmap.org/search.php").unwrap();
+        let client = Client::from_reqwest(reqwest::Client::new(), &Config::default()).offline(true);
+        let result = client.get(&url).await;
+        assert!(matches!(result, Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_retry_after_in_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_retry_after_is_capped() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("86400"));
+        assert_eq!(retry_after(&headers), Some(MAX_RETRY_AFTER));
+    }
+
+    #[test]
+    fn test_retry_after_missing() {
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,626 @@
+use chrono::NaiveDateTime;
+use serde::{self, Deserialize};
+
+/// Languages of the human-readable output (JSON output is always in English).
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+    Pt,
+    De,
+}
+
+/// Message catalogue with the English text and its French, Portuguese and German translations.
+const CATALOG: &[(&str, &str, &str, &str)] = &[
+    // labels
+    ("feels like", "ressenti", "sensação de", "gefühlt"),
+    (
+        "chance of rain",
+        "de risque de pluie",
+        "de chance de chuva",
+        "Regenwahrscheinlichkeit",
+    ),
+    ("humidity", "d'humidité", "de umidade", "Luftfeuchtigkeit"),
+    (
+        "heat index",
+        "indice de chaleur",
+        "índice de calor",
+        "Hitzeindex",
+    ),
+    (
+        "wind chill",
+        "refroidissement éolien",
+        "sensação térmica",
+        "Windchill",
+    ),
+    ("as of", "en date du", "em", "Stand"),
+    ("until", "jusqu'au", "até", "bis"),
+    ("at", "à", "às", "um"),
+    ("of rain", "de pluie", "de chuva", "Regen"),
+    ("UV index", "indice UV", "índice UV", "UV-Index"),
+    (
+        "cloud cover",
+        "de couverture nuageuse",
+        "de nebulosidade",
+        "Bewölkung",
+    ),
+    (
+        "dew point",
+        "point de rosée",
+        "ponto de orvalho",
+        "Taupunkt",
+    ),
+    ("visibility", "de visibilité", "de visibilidade", "Sicht"),
+    // course
+    ("headwind", "vent de face", "vento contra", "Gegenwind"),
+    ("tailwind", "vent arrière", "vento a favor", "Rückenwind"),
+    (
+        "crosswind from the left",
+        "vent latéral de gauche",
+        "vento lateral da esquerda",
+        "Seitenwind von links",
+    ),
+    (
+        "crosswind from the right",
+        "vent latéral de droite",
+        "vento lateral da direita",
+        "Seitenwind von rechts",
+    ),
+    // model comparison
+    ("model", "modèle", "modelo", "Modell"),
+    ("temperature", "température", "temperatura", "Temperatur"),
+    ("wind", "vent", "vento", "Wind"),
+    (
+        "models diverge on",
+        "les modèles divergent sur",
+        "os modelos divergem em",
+        "Modelle weichen ab bei",
+    ),
+    // best start times
+    ("score", "score", "pontuação", "Punktzahl"),
+    ("warm", "chaud", "calor", "warm"),
+    ("cold", "froid", "frio", "kalt"),
+    (
+        "rain likely",
+        "pluie probable",
+        "chuva provável",
+        "Regen wahrscheinlich",
+    ),
+    ("windy", "venteux", "ventoso", "windig"),
+    ("high UV", "UV élevé", "UV alto", "hoher UV-Index"),
+    (
+        "good conditions",
+        "bonnes conditions",
+        "boas condições",
+        "gute Bedingungen",
+    ),
+    // pace
+    ("Goal", "Objectif", "Meta", "Ziel"),
+    ("for", "pour", "por", "für"),
+    ("for heat", "pour la chaleur", "pelo calor", "für Hitze"),
+    (
+        "temperature + dew point",
+        "température + point de rosée",
+        "temperatura + ponto de orvalho",
+        "Temperatur + Taupunkt",
+    ),
+    (
+        "too hot for hard running",
+        "trop chaud pour courir à fond",
+        "quente demais para correr forte",
+        "zu heiß für hartes Laufen",
+    ),
+    // check
+    ("PASS", "OK", "OK", "OK"),
+    ("WARN", "ATTENTION", "ATENÇÃO", "WARNUNG"),
+    ("FAIL", "ÉCHEC", "FALHA", "ABBRUCH"),
+    (
+        "rain probability",
+        "probabilité de pluie",
+        "probabilidade de chuva",
+        "Regenwahrscheinlichkeit",
+    ),
+    ("gusts", "rafales", "rajadas", "Böen"),
+    ("is above", "dépasse", "está acima de", "liegt über"),
+    (
+        "is outside of",
+        "est hors de",
+        "está fora de",
+        "liegt außerhalb von",
+    ),
+    ("to", "à", "a", "bis"),
+    (
+        "thunderstorm expected",
+        "orage prévu",
+        "trovoada prevista",
+        "Gewitter erwartet",
+    ),
+    // daylight
+    (
+        "sunrise",
+        "lever du soleil",
+        "nascer do sol",
+        "Sonnenaufgang",
+    ),
+    (
+        "sunset",
+        "coucher du soleil",
+        "pôr do sol",
+        "Sonnenuntergang",
+    ),
+    (
+        "civil twilight",
+        "crépuscule civil",
+        "crepúsculo civil",
+        "bürgerliche Dämmerung",
+    ),
+    (
+        "in the dark, bring lights",
+        "dans le noir, prévoyez un éclairage",
+        "no escuro, leve luzes",
+        "im Dunkeln, Licht mitnehmen",
+    ),
+    // air quality
+    ("US AQI", "IQA US", "IQA EUA", "US-AQI"),
+    ("EU AQI", "IQA UE", "IQA UE", "EU-AQI"),
+    ("ozone", "ozone", "ozônio", "Ozon"),
+    (
+        "alder pollen",
+        "pollen d'aulne",
+        "pólen de amieiro",
+        "Erlenpollen",
+    ),
+    (
+        "birch pollen",
+        "pollen de bouleau",
+        "pólen de bétula",
+        "Birkenpollen",
+    ),
+    (
+        "grass pollen",
+        "pollen de graminées",
+        "pólen de gramíneas",
+        "Gräserpollen",
+    ),
+    (
+        "mugwort pollen",
+        "pollen d'armoise",
+        "pólen de artemísia",
+        "Beifußpollen",
+    ),
+    (
+        "olive pollen",
+        "pollen d'olivier",
+        "pólen de oliveira",
+        "Olivenpollen",
+    ),
+    (
+        "ragweed pollen",
+        "pollen d'ambroisie",
+        "pólen de ambrósia",
+        "Ambrosiapollen",
+    ),
+    // alert severities
+    ("extreme", "extrême", "extremo", "extrem"),
+    ("severe", "sévère", "severo", "schwer"),
+    ("moderate", "modéré", "moderado", "mäßig"),
+    ("minor", "mineur", "menor", "gering"),
+    ("unknown", "inconnu", "desconhecido", "unbekannt"),
+    // hourly chart
+    ("time", "heure", "hora", "Uhrzeit"),
+    // gear
+    ("run", "course", "corrida", "Laufen"),
+    ("bike", "vélo", "bicicleta", "Radfahren"),
+    ("swim", "natation", "natação", "Schwimmen"),
+    (
+        "sunscreen",
+        "crème solaire",
+        "protetor solar",
+        "Sonnencreme",
+    ),
+    ("cap", "casquette", "boné", "Kappe"),
+    ("tights", "collant", "legging", "Lauftights"),
+    ("gloves", "gants", "luvas", "Handschuhe"),
+    (
+        "rain jacket",
+        "veste de pluie",
+        "jaqueta de chuva",
+        "Regenjacke",
+    ),
+    (
+        "wind vest",
+        "gilet coupe-vent",
+        "colete corta-vento",
+        "Windweste",
+    ),
+    ("arm warmers", "manchettes", "manguitos", "Armlinge"),
+    ("knee warmers", "jambières", "joelheiras", "Knielinge"),
+    (
+        "full-finger gloves",
+        "gants longs",
+        "luvas de dedos longos",
+        "Langfingerhandschuhe",
+    ),
+    (
+        "overshoes",
+        "couvre-chaussures",
+        "protetores de sapatilha",
+        "Überschuhe",
+    ),
+    (
+        "wetsuit",
+        "combinaison",
+        "roupa de neoprene",
+        "Neoprenanzug",
+    ),
+    (
+        "neoprene cap",
+        "bonnet en néoprène",
+        "touca de neoprene",
+        "Neoprenkappe",
+    ),
+    (
+        "tinted goggles",
+        "lunettes teintées",
+        "óculos escuros",
+        "getönte Schwimmbrille",
+    ),
+    // weather descriptions
+    ("clear sky", "ciel dégagé", "céu limpo", "klarer Himmel"),
+    (
+        "mainly clear",
+        "plutôt dégagé",
+        "predominantemente limpo",
+        "überwiegend klar",
+    ),
+    (
+        "partly cloudy",
+        "partiellement nuageux",
+        "parcialmente nublado",
+        "teilweise bewölkt",
+    ),
+    ("overcast", "couvert", "encoberto", "bedeckt"),
+    ("fog", "brouillard", "nevoeiro", "Nebel"),
+    (
+        "depositing rime fog",
+        "brouillard givrant",
+        "nevoeiro com geada",
+        "gefrierender Nebel",
+    ),
+    (
+        "light drizzle",
+        "bruine légère",
+        "garoa fraca",
+        "leichter Nieselregen",
+    ),
+    (
+        "moderate drizzle",
+        "bruine modérée",
+        "garoa moderada",
+        "mäßiger Nieselregen",
+    ),
+    (
+        "dense drizzle",
+        "bruine dense",
+        "garoa densa",
+        "dichter Nieselregen",
+    ),
+    (
+        "light freezing drizzle",
+        "bruine verglaçante légère",
+        "garoa congelante fraca",
+        "leichter gefrierender Nieselregen",
+    ),
+    (
+        "dense freezing drizzle",
+        "bruine verglaçante dense",
+        "garoa congelante densa",
+        "dichter gefrierender Nieselregen",
+    ),
+    (
+        "slight rain",
+        "pluie faible",
+        "chuva fraca",
+        "leichter Regen",
+    ),
+    (
+        "moderate rain",
+        "pluie modérée",
+        "chuva moderada",
+        "mäßiger Regen",
+    ),
+    ("heavy rain", "forte pluie", "chuva forte", "starker Regen"),
+    (
+        "light freezing rain",
+        "pluie verglaçante faible",
+        "chuva congelante fraca",
+        "leichter gefrierender Regen",
+    ),
+    (
+        "heavy freezing rain",
+        "forte pluie verglaçante",
+        "chuva congelante forte",
+        "starker gefrierender Regen",
+    ),
+    (
+        "slight snow fall",
+        "faibles chutes de neige",
+        "neve fraca",
+        "leichter Schneefall",
+    ),
+    (
+        "moderate snow fall",
+        "chutes de neige modérées",
+        "neve moderada",
+        "mäßiger Schneefall",
+    ),
+    (
+        "heavy snow fall",
+        "fortes chutes de neige",
+        "neve forte",
+        "starker Schneefall",
+    ),
+    (
+        "snow grains",
+        "neige en grains",
+        "grãos de neve",
+        "Schneegriesel",
+    ),
+    (
+        "slight rain showers",
+        "faibles averses de pluie",
+        "pancadas de chuva fracas",
+        "leichte Regenschauer",
+    ),
+    (
+        "moderate rain showers",
+        "averses de pluie modérées",
+        "pancadas de chuva moderadas",
+        "mäßige Regenschauer",
+    ),
+    (
+        "violent rain showers",
+        "violentes averses de pluie",
+        "pancadas de chuva violentas",
+        "heftige Regenschauer",
+    ),
+    (
+        "slight snow showers",
+        "faibles averses de neige",
+        "pancadas de neve fracas",
+        "leichte Schneeschauer",
+    ),
+    (
+        "heavy snow showers",
+        "fortes averses de neige",
+        "pancadas de neve fortes",
+        "starke Schneeschauer",
+    ),
+    ("thunderstorm", "orage", "trovoada", "Gewitter"),
+    (
+        "thunderstorm with slight hail",
+        "orage avec faible grêle",
+        "trovoada com granizo fraco",
+        "Gewitter mit leichtem Hagel",
+    ),
+    (
+        "thunderstorm with heavy hail",
+        "orage avec forte grêle",
+        "trovoada com granizo forte",
+        "Gewitter mit starkem Hagel",
+    ),
+    (
+        "no precipitation",
+        "pas de précipitations",
+        "sem precipitação",
+        "kein Niederschlag",
+    ),
+    (
+        "precipitation in the past hour",
+        "précipitations dans la dernière heure",
+        "precipitação na última hora",
+        "Niederschlag in der letzten Stunde",
+    ),
+    (
+        "duststorm, sandstorm or blowing snow",
+        "tempête de poussière, de sable ou poudrerie",
+        "tempestade de poeira, de areia ou neve soprada",
+        "Staubsturm, Sandsturm oder Schneetreiben",
+    ),
+    ("drizzle", "bruine", "garoa", "Nieselregen"),
+    ("rain", "pluie", "chuva", "Regen"),
+    ("snow", "neige", "neve", "Schnee"),
+    ("showers", "averses", "pancadas", "Schauer"),
+    (
+        "unknown weather",
+        "temps inconnu",
+        "tempo desconhecido",
+        "unbekanntes Wetter",
+    ),
+    // Beaufort scale
+    ("force", "force", "força", "Stärke"),
+    ("calm", "calme", "calmaria", "Windstille"),
+    ("light air", "très légère brise", "aragem", "leiser Zug"),
+    (
+        "light breeze",
+        "légère brise",
+        "brisa leve",
+        "leichte Brise",
+    ),
+    (
+        "gentle breeze",
+        "petite brise",
+        "brisa fraca",
+        "schwache Brise",
+    ),
+    (
+        "moderate breeze",
+        "jolie brise",
+        "brisa moderada",
+        "mäßige Brise",
+    ),
+    (
+        "fresh breeze",
+        "bonne brise",
+        "brisa forte",
+        "frische Brise",
+    ),
+    (
+        "strong breeze",
+        "vent frais",
+        "vento fresco",
+        "starker Wind",
+    ),
+    ("near gale", "grand frais", "vento forte", "steifer Wind"),
+    ("gale", "coup de vent", "ventania", "stürmischer Wind"),
+    (
+        "strong gale",
+        "fort coup de vent",
+        "ventania forte",
+        "Sturm",
+    ),
+    ("storm", "tempête", "tempestade", "schwerer Sturm"),
+    (
+        "violent storm",
+        "violente tempête",
+        "tempestade violenta",
+        "orkanartiger Sturm",
+    ),
+    ("hurricane force", "ouragan", "furacão", "Orkan"),
+];
+
+/// Letters of the compass points (north, east, south, west and "by") in each language.
+const COMPASS: &[(char, char, char, char, char)] = &[
+    ('N', 'N', 'N', 'N', 'N'),
+    ('E', 'E', 'E', 'L', 'O'),
+    ('S', 'S', 'S', 'S', 'S'),
+    ('W', 'W', 'O', 'O', 'W'),
+    ('b', 'b', 'q', 'q', 'z'),
+];
+
+impl Locale {
+    /// Parses locales such as `fr`, `pt_BR.UTF-8` or `de-DE`.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['_', '-', '.']).next()?.to_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "fr" => Some(Locale::Fr),
+            "pt" => Some(Locale::Pt),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    /// Locale from the `LC_ALL` or `LANG` environment variables.
+    pub fn from_env() -> Option<Self> {
+        ["LC_ALL", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_tag(&value))
+    }
+
+    /// Translates a text from the catalogue, or returns it as is when it is not in there.
+    pub fn t<'a>(&self, text: &'a str) -> &'a str {
+        let Some((en, fr, pt, de)) = CATALOG.iter().find(|(en, ..)| *en == text) else {
+            return text;
+        };
+        match self {
+            Locale::En => en,
+            Locale::Fr => fr,
+            Locale::Pt => pt,
+            Locale::De => de,
+        }
+    }
+
+    /// Translates a compass point letter by letter, e.g. `SW` is `SO` in French.
+    pub fn direction(&self, direction: &str) -> String {
+        direction
+            .chars()
+            .map(|letter| {
+                let Some((_, en, fr, pt, de)) = COMPASS.iter().find(|(key, ..)| *key == letter)
+                else {
+                    return letter;
+                };
+                match self {
+                    Locale::En => *en,
+                    Locale::Fr => *fr,
+                    Locale::Pt => *pt,
+                    Locale::De => *de,
+                }
+            })
+            .collect()
+    }
+
+    /// Date and time with the month name in this language, e.g. `May 20, 08:00` or `20 mai, 08:00`.
+    pub fn date(&self, date: &NaiveDateTime) -> String {
+        let (format, locale) = match self {
+            Locale::En => ("%b %-d, %H:%M", chrono::Locale::en_US),
+            Locale::Fr => ("%-d %b, %H:%M", chrono::Locale::fr_CA),
+            Locale::Pt => ("%-d de %b, %H:%M", chrono::Locale::pt_BR),
+            Locale::De => ("%-d. %b, %H:%M", chrono::Locale::de_DE),
+        };
+        date.and_utc().format_localized(format, locale).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wmo;
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("fr_CA.UTF-8"), Some(Locale::Fr));
+        assert_eq!(Locale::from_tag("pt-BR"), Some(Locale::Pt));
+        assert_eq!(Locale::from_tag("de"), Some(Locale::De));
+        assert_eq!(Locale::from_tag("C"), None);
+    }
+
+    #[test]
+    fn test_locale_t() {
+        assert_eq!(Locale::En.t("feels like"), "feels like");
+        assert_eq!(Locale::Fr.t("feels like"), "ressenti");
+        assert_eq!(Locale::Pt.t("not in the catalog"), "not in the catalog");
+    }
+
+    #[test]
+    fn test_catalog_has_no_duplicates() {
+        for (idx, (en, ..)) in CATALOG.iter().enumerate() {
+            assert!(
+                CATALOG[idx + 1..].iter().all(|(other, ..)| other != en),
+                "{en} is in the catalog more than once"
+            );
+        }
+    }
+
+    #[test]
+    fn test_locale_direction() {
+        assert_eq!(Locale::De.direction("SW"), "SW");
+        assert_eq!(Locale::Pt.direction("E"), "L");
+        assert_eq!(Locale::Fr.direction("WNW"), "ONO");
+        assert_eq!(Locale::De.direction("NEbN"), "NOzN");
+    }
+
+    #[test]
+    fn test_catalog_has_every_weather_description() {
+        for code in -1..=100 {
+            let description = wmo::description(code);
+            assert!(
+                CATALOG.iter().any(|(en, ..)| *en == description),
+                "{description} is missing from the catalog"
+            );
+        }
+    }
+
+    #[test]
+    fn test_locale_date() {
+        let date = NaiveDateTime::parse_from_str("2021-05-20 08:00", "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(Locale::En.date(&date), "May 20, 08:00");
+        assert_eq!(Locale::Fr.date(&date), "20 mai, 08:00");
+        assert_eq!(Locale::Pt.date(&date), "20 de mai, 08:00");
+        assert_eq!(Locale::De.date(&date), "20. Mai, 08:00");
+    }
+}
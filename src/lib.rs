@@ -1,22 +1,55 @@
-use anyhow::{Context, Result};
 use chrono::NaiveDateTime;
+use course::Waypoint;
+use http::Client;
 use units::Units;
 use weather::Weather;
 
+pub use error::{Error, Result};
+
+pub mod air;
+pub mod alerts;
+pub mod best;
+pub mod cache;
+pub mod check;
+pub mod compare;
+pub mod config;
+pub mod course;
+pub mod daylight;
+pub mod ensemble;
+pub mod error;
+pub mod gear;
+pub mod hourly;
+pub mod http;
+pub mod i18n;
+pub mod output;
+pub mod pace;
 pub mod persistence;
+pub mod stress;
+pub mod template;
 pub mod units;
 pub mod weather;
+pub mod wind;
 
 mod date_format;
 mod emoji;
 mod geo;
-mod wind;
+mod open_meteo;
+mod throttle;
+mod wmo;
 
 pub const DATE_INPUT_FORMAT: &str = "%Y-%m-%d %H:%M";
 
-fn date_parser(value: &String) -> Result<NaiveDateTime> {
-    NaiveDateTime::parse_from_str(value, DATE_INPUT_FORMAT).with_context(|| {
-        format!("Failed to parse date and time, it should be in the format {DATE_INPUT_FORMAT}: {value}")
+/// Days ahead covered by the forecast.
+pub const FORECAST_DAYS: i64 = 16;
+
+/// Whole days from now until `when`, negative for the past.
+pub fn days_from_now(when: NaiveDateTime) -> i64 {
+    (when - chrono::Local::now().naive_local()).num_days()
+}
+
+pub fn date_parser(value: &str) -> Result<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, DATE_INPUT_FORMAT).map_err(|_| {
+        Error::Parse(format!("Failed to parse date and time, it should be in the format {DATE_INPUT_FORMAT}: {value}"))
     })
 }
 
@@ -28,12 +61,25 @@ pub struct Event {
     latitude: f64,
     longitude: f64,
     days: i64,
+
+    /// Bearings of the legs of the course, in degrees
+    pub bearings: Vec<f64>,
+
+    /// Waypoints sampled along the course, the target speed in km/h and the distance in km
+    pub course: Vec<Waypoint>,
+    pub speed: Option<f64>,
+    pub distance: Option<f64>,
 }
 
 impl Event {
-    pub async fn new(name: Option<String>, date: String, location: String) -> Result<Self> {
+    pub async fn new(
+        name: Option<String>,
+        date: String,
+        location: String,
+        client: &Client,
+    ) -> Result<Self> {
         let when = date_parser(&date)?;
-        let (latitude, longitude) = geo::coordinates(&location).await?;
+        let (latitude, longitude) = geo::coordinates(&location, client).await?;
 
         Ok(Self {
             name,
@@ -41,51 +87,98 @@ impl Event {
             location,
             latitude,
             longitude,
-            days: (when - chrono::Local::now().naive_local()).num_days(),
+            days: days_from_now(when),
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         })
     }
 
-    pub fn has_weather_forecast(&self, verbose: bool) -> bool {
-        if self.days < 0 {
-            if verbose {
-                match &self.name {
-                    Some(name) => println!(
-                        "Skipping weather forecast for {} since it was {} days ago.",
-                        name, -self.days
-                    ),
-                    None => println!("Skipping weather forecast for {} days ago.", -self.days),
-                };
+    /// Estimated end of the event, from its distance and goal speed when there are both.
+    pub fn end(&self) -> NaiveDateTime {
+        match (self.distance, self.speed) {
+            (Some(distance), Some(speed)) if speed > 0.0 => {
+                self.when + chrono::Duration::seconds((distance / speed * 3_600.0).round() as i64)
             }
-            return false;
+            _ => self.when,
+        }
+    }
+
+    pub fn with_bearings(mut self, bearings: Vec<f64>) -> Self {
+        self.bearings = bearings;
+        self
+    }
+
+    /// The distance of the event becomes the length of the course.
+    pub fn with_course(mut self, course: Vec<Waypoint>) -> Self {
+        self.distance = course.last().map(|waypoint| waypoint.distance);
+        self.course = course;
+        self
+    }
+
+    pub fn with_distance(mut self, distance: f64) -> Self {
+        self.distance = Some(distance);
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// Errors when the event is in the past or beyond the 16 days of the forecast.
+    pub fn validate_forecast_range(&self) -> Result<()> {
+        if self.days < 0 {
+            return Err(Error::Validation(match &self.name {
+                Some(name) => format!(
+                    "Skipping weather forecast for {} since it was {} days ago.",
+                    name, -self.days
+                ),
+                None => format!("Skipping weather forecast for {} days ago.", -self.days),
+            }));
+        }
+        if self.days >= FORECAST_DAYS {
+            return Err(Error::Validation(match &self.name {
+                Some(name) => format!(
+                    "Skipping weather forecast for {} since it is {} days in the future.",
+                    name, self.days
+                ),
+                None => format!(
+                    "Skipping weather forecast for {} days in the future.",
+                    self.days
+                ),
+            }));
         }
-        if self.days >= 16 {
-            if verbose {
-                match &self.name {
-                    Some(name) => println!(
-                        "Skipping weather forecast for {} since it is {} days in the future.",
-                        name, self.days
-                    ),
-
-                    None => println!(
-                        "Skipping weather forecast for {} days in the future.",
-                        self.days
-                    ),
-                };
+        Ok(())
+    }
+
+    pub fn has_weather_forecast(&self, verbose: bool) -> bool {
+        match self.validate_forecast_range() {
+            Ok(()) => true,
+            Err(e) => {
+                if verbose {
+                    println!("{e}");
+                }
+                false
             }
-            return false;
         }
-        true
     }
 
-    pub async fn weather(&self, units: &Units) -> Result<Weather> {
-        Weather::new(
+    pub async fn weather(&self, units: &Units, client: &Client) -> Result<Weather<'_>> {
+        let mut weather = Weather::new(
             self.when,
             self.latitude,
             self.longitude,
             units,
             self.name.clone(),
             self.location.clone(),
+            client,
         )
-        .await
+        .await?;
+        weather.along(&self.bearings);
+        weather.along_course(self, client).await?;
+        weather.window(self.when, self.end());
+        Ok(weather)
     }
 }
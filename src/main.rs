@@ -1,10 +1,28 @@
-use std::process::Command;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::process::{Command, ExitCode};
+use std::slice;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use chrono::{Duration, NaiveTime};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+use wed::air;
+use wed::best;
+use wed::check::{Check, Thresholds, Verdict};
+use wed::compare;
+use wed::config::Config;
+use wed::gear::{Rules, Sport};
+use wed::hourly;
+use wed::http::Client;
+use wed::i18n::Locale;
+use wed::output::{Field, Format, Options, Renderer};
+use wed::pace::Pace;
 use wed::persistence::{SavedEvent, SavedEvents};
-use wed::units::Units;
-use wed::weather::Notification;
+use wed::template::Template;
+use wed::units::{Distance, Precipitation, Preset, Speed, Temperature, Units};
+use wed::weather::{Notification, Weather};
 use wed::Event;
 
 const MACOS_NOTIFICATION_COMMAND: &str = "on run argv
@@ -19,18 +37,111 @@ struct Args {
     #[arg(short, long)]
     json: bool,
 
+    /// Output format of the weather forecast (`--json` is the same as `--format json`)
+    #[arg(long, conflicts_with = "json")]
+    format: Option<Format>,
+
+    /// Template for the output (e.g. `"{{ icon }} {{ temperature | round | int }}°"`), or the name
+    /// of one of the templates from the config file
+    #[arg(short, long, conflicts_with_all = ["json", "format"])]
+    template: Option<String>,
+
     /// Output more information about the internal state of the application
     #[arg(short, long)]
     verbose: bool,
 
+    /// Use the last cached forecast instead of making new requests
+    #[arg(short, long)]
+    offline: bool,
+
     /// Units to use for the weather forecast
     #[arg(short, long)]
-    units: Option<Units>,
+    units: Option<Preset>,
+
+    /// Temperature unit, overriding the one from --units
+    #[arg(long)]
+    temperature_unit: Option<Temperature>,
+
+    /// Wind speed unit, overriding the one from --units
+    #[arg(long)]
+    wind_unit: Option<Speed>,
+
+    /// Precipitation unit, overriding the one from --units
+    #[arg(long)]
+    precipitation_unit: Option<Precipitation>,
+
+    /// Distance unit, overriding the one from --units
+    #[arg(long)]
+    distance_unit: Option<Distance>,
+
+    /// Extra weather variables to show in the human-readable output (comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    fields: Vec<Field>,
+
+    /// Sports to recommend clothing and gear for (comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    sport: Vec<Sport>,
+
+    /// Show the air quality and pollens for events in the next 5 days
+    #[arg(short, long)]
+    air_quality: bool,
+
+    /// Show the 10th–90th percentile range of the ensemble forecast for temperature,
+    /// precipitation and wind
+    #[arg(short, long)]
+    ensemble: bool,
+
+    /// Weather models to compare side by side, e.g. ecmwf_ifs025,gfs_seamless,icon_seamless
+    /// (comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    models: Vec<String>,
 
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+impl Args {
+    /// Name of what only has a text and a JSON output, when it is requested.
+    fn text_or_json_only(&self) -> Option<&'static str> {
+        match &self.command {
+            Some(Commands::Notify {}) => Some("notify"),
+            Some(Commands::Best { .. }) => Some("best"),
+            Some(Commands::Pace { .. }) => Some("pace"),
+            Some(Commands::Check { .. }) => Some("check"),
+            Some(Commands::List {}) | Some(Commands::Delete { .. }) => None,
+            _ if !self.models.is_empty() => Some("--models"),
+            _ => None,
+        }
+    }
+
+    /// Name of what has its own output instead of the template, when it is requested.
+    fn without_template(&self) -> Option<&'static str> {
+        match &self.command {
+            Some(Commands::Notify {}) => None,
+            _ => self.text_or_json_only(),
+        }
+    }
+
+    /// Rejects the output formats and templates that the command does not support.
+    fn validate(&self) -> std::result::Result<(), clap::Error> {
+        if let (Some(name), Some(_)) = (self.without_template(), &self.template) {
+            return Err(Args::command().error(
+                ErrorKind::ArgumentConflict,
+                format!("{name} does not support --template"),
+            ));
+        }
+        if let (Some(name), Some(format)) = (self.text_or_json_only(), self.format) {
+            if !matches!(format, Format::Text | Format::Json) {
+                return Err(Args::command().error(
+                    ErrorKind::ArgumentConflict,
+                    format!("{name} only supports `--format text` and `--format json`"),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// List saved events
@@ -44,13 +155,78 @@ enum Commands {
         name: String,
         location: String,
         when: String,
+
+        /// Bearing of each leg of the course in degrees, to break the wind down into headwind
+        /// and crosswind (comma-separated)
+        #[arg(short, long, value_delimiter = ',', allow_negative_numbers = true)]
+        bearing: Vec<f64>,
+
+        /// GPX or TCX file with the course, to forecast the conditions along it
+        #[arg(short, long)]
+        course: Option<PathBuf>,
+
+        /// Goal pace (mm:ss per km, or per mile with miles as the distance unit)
+        #[arg(long, conflicts_with_all = ["speed", "finish"])]
+        pace: Option<String>,
+
+        /// Goal speed (km/h, or mph with miles as the distance unit)
+        #[arg(long, conflicts_with = "finish")]
+        speed: Option<f64>,
+
+        /// Goal finish time (h:mm:ss), which requires the distance or the course
+        #[arg(long)]
+        finish: Option<String>,
+
+        /// Distance of the event (km, or miles with miles as the distance unit), unless there is a course
+        #[arg(long, conflicts_with = "course")]
+        distance: Option<f64>,
     },
 
     /// Show the forecast for a given location, date and time
-    Forecast { location: String, when: String },
+    Forecast {
+        location: String,
+        when: String,
+
+        /// Bearing of each leg of the course in degrees, to break the wind down into headwind
+        /// and crosswind (comma-separated)
+        #[arg(short, long, value_delimiter = ',', allow_negative_numbers = true)]
+        bearing: Vec<f64>,
+
+        /// Show every hour of the event date, with sparklines for temperature, rain and wind
+        #[arg(long)]
+        hourly: bool,
+    },
 
     /// Display a notification on macOS (defaults to JSON output on other OS)
     Notify {},
+
+    /// Find the best start times between two dates and times for an activity of a given duration
+    Best {
+        location: String,
+
+        /// Earliest start time
+        #[arg(long)]
+        from: String,
+
+        /// Latest start time
+        #[arg(long)]
+        to: String,
+
+        /// Duration of the activity in hours
+        #[arg(long, default_value_t = 1)]
+        duration: u8,
+
+        /// Number of start times to show
+        #[arg(long, default_value_t = 3)]
+        top: usize,
+    },
+
+    /// Adjust the goal pace of a saved event for heat, humidity and wind
+    Pace { name: String },
+
+    /// Check the forecast of a saved event against the thresholds from the configuration file
+    /// (exits with 0 for pass, 10 for warn and 11 for fail)
+    Check { name: String },
 }
 
 async fn load_saved_events(verbose: bool) -> Result<SavedEvents> {
@@ -76,42 +252,135 @@ async fn list_saved_events(verbose: bool) -> Result<()> {
     Ok(())
 }
 
-async fn forecast_for_saved_events(units: &Units, verbose: bool, json: bool) -> Result<()> {
-    let saved = load_saved_events(verbose)
+/// Adds what depends on the options to the forecast: gear, alerts and the ensemble spread.
+async fn enrich(weather: &mut Weather<'_>, event: &Event, client: &Client, options: &Options) {
+    weather.recommend(&options.rules, &options.sports);
+    // the forecast is still useful without the alerts or the ensemble spread
+    if let Err(e) = weather
+        .alert(event, options.alerts.as_deref(), client)
+        .await
+    {
+        eprintln!("Warning: failed to fetch the weather alerts: {e}");
+    }
+    if options.ensemble {
+        if let Err(e) = weather.ensemble(event, client).await {
+            eprintln!("Warning: failed to fetch the ensemble forecast: {e}");
+        }
+    }
+}
+
+/// Adds the air quality to the forecasts of the events with a single request, when asked for.
+async fn add_air_quality(
+    forecasts: &mut [Weather<'_>],
+    events: &[Event],
+    client: &Client,
+    options: &Options,
+) {
+    if !options.air {
+        return;
+    }
+    // the forecast is still useful without the air quality
+    match air::for_events(events, client).await {
+        Ok(air_quality) => {
+            for (weather, air_quality) in forecasts.iter_mut().zip(air_quality) {
+                weather.air_quality = air_quality;
+            }
+        }
+        Err(e) => eprintln!("Warning: failed to fetch the air quality: {e}"),
+    }
+}
+
+async fn forecast_for_saved_events(
+    units: &Units,
+    client: &Client,
+    options: &Options,
+    verbose: bool,
+    format: Format,
+) -> Result<()> {
+    let events = load_saved_events(verbose)
         .await?
         .events
         .into_iter()
         .map(|data| data.to_event())
-        .filter(|event| event.has_weather_forecast(verbose));
-
-    let mut output: Vec<String> = Vec::new();
-    let mut tasks = vec![];
+        .filter(|event| event.has_weather_forecast(verbose))
+        .collect::<Vec<Event>>();
 
-    for event in saved {
-        let unit = units.clone();
-        tasks.push(tokio::spawn(async move {
-            event.weather(&unit).await?.as_string(json)
-        }));
+    if !options.models.is_empty() {
+        let mut comparisons = vec![];
+        for event in &events {
+            comparisons.push(compare::compare(event, &options.models, units, client).await?);
+        }
+        if format == Format::Json {
+            println!("{}", serde_json::to_string(&comparisons)?);
+        } else if !comparisons.is_empty() {
+            let output = comparisons
+                .iter()
+                .map(|models| compare::as_string(models, false, options))
+                .collect::<wed::Result<Vec<String>>>()?;
+            println!("{}", output.join("\n\n"));
+        }
+        return Ok(());
     }
 
-    for task in tasks {
-        let result: Result<String> = task.await?;
-        output.push(result?);
+    let mut forecasts = Weather::for_events(&events, units, client).await?;
+    add_air_quality(&mut forecasts, &events, client, options).await;
+    for (weather, event) in forecasts.iter_mut().zip(&events) {
+        enrich(weather, event, client, options).await;
     }
+    if !forecasts.is_empty() {
+        println!("{}", options.renderer(format).render(&forecasts, options)?);
+    }
+    Ok(())
+}
 
-    if !output.is_empty() {
-        if json {
-            println!("[{}]", output.join(","));
-        } else {
-            println!("{}", output.join("\n\n"));
-        }
+async fn forecast_for(
+    event: &Event,
+    units: &Units,
+    client: &Client,
+    options: &Options,
+    format: Format,
+    verbose: bool,
+) -> Result<()> {
+    if event.has_weather_forecast(verbose) && !options.models.is_empty() {
+        let models = compare::compare(event, &options.models, units, client).await?;
+        let json = format == Format::Json;
+        println!("{}", compare::as_string(&models, json, options)?);
+    } else if event.has_weather_forecast(verbose) {
+        let mut weather = event.weather(units, client).await?;
+        add_air_quality(
+            slice::from_mut(&mut weather),
+            slice::from_ref(event),
+            client,
+            options,
+        )
+        .await;
+        enrich(&mut weather, event, client, options).await;
+        println!(
+            "{}",
+            options.renderer(format).render_one(&weather, options)?
+        );
     }
     Ok(())
 }
 
-async fn forecast_for(event: &Event, units: &Units, json: bool, verbose: bool) -> Result<()> {
-    if event.has_weather_forecast(verbose) {
-        println!("{}", event.weather(units).await?.as_string(json)?);
+async fn hourly_forecast_for(
+    event: &Event,
+    units: &Units,
+    client: &Client,
+    options: &Options,
+    format: Format,
+) -> Result<()> {
+    let from = event.when.date().and_time(NaiveTime::MIN);
+    let to = from + Duration::try_hours(23).unwrap_or_default();
+    let series = Weather::series(event, from, to, units, client).await?;
+    if format == Format::Text && options.template.is_none() {
+        let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+        println!(
+            "{}",
+            hourly::as_string(&series, event.when, color, options)?
+        );
+    } else if !series.is_empty() {
+        println!("{}", options.renderer(format).render(&series, options)?);
     }
     Ok(())
 }
@@ -122,39 +391,60 @@ async fn save_event(event: &Event) -> Result<()> {
         Err(_) => SavedEvents::new(),
     };
     events.add(SavedEvent::from_event(event)?);
-    events.to_file()
+    Ok(events.to_file()?)
 }
 
 async fn delete_event(name: &str, verbose: bool) -> Result<()> {
     let mut saved = load_saved_events(verbose).await?;
     saved.events.retain(|event| event.name != name);
-    saved.to_file()
+    Ok(saved.to_file()?)
 }
 
-async fn load_notification(units: &Units, verbose: bool) -> Option<Notification> {
-    let events = load_saved_events(verbose)
-        .await
-        .ok()?
+async fn load_notification(
+    units: &Units,
+    client: &Client,
+    options: &Options,
+    verbose: bool,
+) -> Result<Option<Notification>> {
+    let Ok(saved) = load_saved_events(verbose).await else {
+        return Ok(None);
+    };
+    let events = saved
         .events
         .into_iter()
         .map(|data| data.to_event())
         .filter(|event| event.has_weather_forecast(verbose))
         .collect::<Vec<Event>>();
     if events.is_empty() {
-        return None;
+        return Ok(None);
+    }
+    // without a forecast there is just no notification, but a broken template is an error
+    let Ok(mut weather) = events[0].weather(units, client).await else {
+        return Ok(None);
+    };
+    add_air_quality(slice::from_mut(&mut weather), &events[..1], client, options).await;
+    enrich(&mut weather, &events[0], client, options).await;
+    let mut notification = weather.as_notification(options)?;
+    if let Some(template) = &options.template {
+        notification.body = template.render_one(&weather, options)?;
     }
-    events[0].weather(units).await.ok()?.as_notification().ok()
+    Ok(Some(notification))
 }
 
-async fn json_notification(units: &Units, verbose: bool) -> Result<()> {
-    if let Some(notification) = load_notification(units, verbose).await {
+async fn json_notification(
+    units: &Units,
+    client: &Client,
+    options: &Options,
+    verbose: bool,
+) -> Result<()> {
+    if let Some(notification) = load_notification(units, client, options, verbose).await? {
         println!("{}", serde_json::to_string(&notification)?);
     }
     Ok(())
 }
 
-async fn macos_notification(units: &Units) -> Result<()> {
-    if let Some(notification) = load_notification(units, false).await {
+async fn macos_notification(units: &Units, client: &Client, options: &Options) -> Result<()> {
+    if let Some(notification) = load_notification(units, client, options, false).await? {
         Command::new("osascript")
             .arg("-e")
             .arg(MACOS_NOTIFICATION_COMMAND)
@@ -166,34 +456,254 @@ async fn macos_notification(units: &Units) -> Result<()> {
     Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
-    let units = args.units.unwrap_or(Units::Metric);
+async fn pace_for(
+    name: &str,
+    units: &Units,
+    client: &Client,
+    locale: &Locale,
+    json: bool,
+) -> Result<()> {
+    let saved = load_saved_events(false).await?;
+    let event = saved
+        .get(name)
+        .ok_or(wed::Error::Validation(format!(
+            "No saved event named {name}"
+        )))?
+        .to_event();
+    let speed = event.speed.ok_or(wed::Error::Validation(format!(
+        "No goal for {name}, save it with --pace, --speed or --finish"
+    )))?;
+    event.validate_forecast_range()?;
+    let weather = event.weather(units, client).await?;
+    let pace = Pace::new(&weather, speed, event.distance);
+    println!("{}", pace.as_string(units, locale, json)?);
+    Ok(())
+}
+
+async fn check_event(
+    name: &str,
+    units: &Units,
+    client: &Client,
+    thresholds: &Thresholds,
+    locale: &Locale,
+    json: bool,
+) -> Result<Verdict> {
+    let saved = load_saved_events(false).await?;
+    let event = saved
+        .get(name)
+        .ok_or(wed::Error::Validation(format!(
+            "No saved event named {name}"
+        )))?
+        .to_event();
+    event.validate_forecast_range()?;
+    let weather = event.weather(units, client).await?;
+    let check = Check::new(&weather, thresholds);
+    println!("{}", check.as_string(locale, json)?);
+    Ok(check.verdict)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn best_start_times(
+    location: &str,
+    from: &str,
+    to: &str,
+    duration: u8,
+    top: usize,
+    units: &Units,
+    client: &Client,
+    locale: &Locale,
+    json: bool,
+) -> Result<()> {
+    let event = Event::new(None, from.to_string(), location.to_string(), client).await?;
+    event.validate_forecast_range()?;
+    let to = wed::date_parser(to)?;
+    if to < event.when {
+        return Err(wed::Error::Validation(format!(
+            "The latest start time should be after the earliest one: {}",
+            to.format(wed::DATE_INPUT_FORMAT)
+        ))
+        .into());
+    }
+    if wed::days_from_now(to) >= wed::FORECAST_DAYS {
+        return Err(wed::Error::Validation(format!(
+            "The latest start time is beyond the {} days of the forecast: {}",
+            wed::FORECAST_DAYS,
+            to.format(wed::DATE_INPUT_FORMAT)
+        ))
+        .into());
+    }
+    let until = to + Duration::try_hours(i64::from(duration.max(1)) - 1).unwrap_or_default();
+    let series = Weather::series(&event, event.when, until, units, client).await?;
+    let slots = best::best(&series, duration.into(), top);
+    println!("{}", best::as_string(&slots, units, locale, json)?);
+    Ok(())
+}
+
+/// Returns the exit code, which is not zero only when `wed check` warns or fails.
+async fn run(args: Args) -> Result<i32> {
+    let preset = Units::from(args.units.unwrap_or(Preset::Metric));
+    let units = Units {
+        temperature: args.temperature_unit.unwrap_or(preset.temperature),
+        speed: args.wind_unit.unwrap_or(preset.speed),
+        precipitation: args.precipitation_unit.unwrap_or(preset.precipitation),
+        distance: args.distance_unit.unwrap_or(preset.distance),
+    };
+    let format = args.format.unwrap_or(if args.json {
+        Format::Json
+    } else {
+        Format::Text
+    });
+    let json = format == Format::Json;
+    // listing and deleting saved events need neither the configuration nor the network
+    match &args.command {
+        Some(Commands::List {}) => return list_saved_events(args.verbose).await.map(|_| 0),
+        Some(Commands::Delete { name }) => {
+            return delete_event(name, args.verbose).await.map(|_| 0)
+        }
+        _ => {}
+    }
+    let config = Config::from_file()?;
+    let client = Client::new(&config)?.offline(args.offline);
+    let options = Options {
+        fields: args.fields.clone(),
+        sports: args.sport.clone(),
+        rules: if args.sport.is_empty() {
+            Rules::default()
+        } else {
+            Rules::from_file()?
+        },
+        air_quality: config.air_quality.clone(),
+        air: args.air_quality,
+        alerts: config.alerts.clone(),
+        ensemble: args.ensemble,
+        models: args.models.clone(),
+        divergence: config.divergence.clone(),
+        locale: config.locale.or_else(Locale::from_env).unwrap_or_default(),
+        wind: config.wind.clone(),
+        template: args
+            .template
+            .as_deref()
+            .map(|template| Template::new(template, &config.templates)),
+    };
     match &args.command {
-        None => forecast_for_saved_events(&units, args.verbose, args.json).await,
-        Some(Commands::List {}) => list_saved_events(args.verbose).await,
-        Some(Commands::Delete { name }) => delete_event(name, args.verbose).await,
-        Some(Commands::Forecast { location, when }) => {
-            let event = Event::new(None, when.clone(), location.clone()).await?;
-            forecast_for(&event, &units, args.json, args.verbose).await
+        None => forecast_for_saved_events(&units, &client, &options, args.verbose, format).await,
+        Some(Commands::List {}) | Some(Commands::Delete { .. }) => unreachable!(),
+        Some(Commands::Forecast {
+            location,
+            when,
+            bearing,
+            hourly,
+        }) => {
+            let event = Event::new(None, when.clone(), location.clone(), &client)
+                .await?
+                .with_bearings(bearing.clone());
+            if !*hourly {
+                forecast_for(&event, &units, &client, &options, format, args.verbose).await
+            } else if event.has_weather_forecast(args.verbose) {
+                hourly_forecast_for(&event, &units, &client, &options, format).await
+            } else {
+                Ok(())
+            }
         }
         Some(Commands::Save {
             name,
             location,
             when,
+            bearing,
+            course,
+            pace,
+            speed,
+            finish,
+            distance,
         }) => {
-            let event = Event::new(Some(name.clone()), when.clone(), location.clone()).await?;
-            forecast_for(&event, &units, args.json, args.verbose).await?;
+            let mut event = Event::new(Some(name.clone()), when.clone(), location.clone(), &client)
+                .await?
+                .with_bearings(bearing.clone());
+            if let Some(path) = course {
+                event = event.with_course(wed::course::from_file(path)?);
+            }
+            if let Some(distance) = distance {
+                event = event.with_distance(units.to_km(*distance));
+            }
+            let speed = match (pace, speed, finish, event.distance) {
+                (Some(pace), _, _, _) => Some(wed::pace::speed_from_pace(pace, &units)?),
+                (_, Some(speed), _, _) => Some(wed::pace::goal_speed(*speed, &units)?),
+                (_, _, Some(finish), Some(distance)) => {
+                    Some(distance / wed::pace::seconds(finish)? * 3_600.0)
+                }
+                (_, _, Some(_), None) => {
+                    return Err(wed::Error::Validation(
+                        "A --distance or a --course is needed to use a finish time".to_string(),
+                    )
+                    .into())
+                }
+                _ => None,
+            };
+            match speed {
+                Some(speed) => event = event.with_speed(speed),
+                None if !event.course.is_empty() => {
+                    return Err(wed::Error::Validation(
+                        "A goal --pace, --speed or --finish is needed to estimate the arrival times along the course".to_string(),
+                    )
+                    .into())
+                }
+                None => {}
+            }
+            forecast_for(&event, &units, &client, &options, format, args.verbose).await?;
             save_event(&event).await
         }
 
         Some(Commands::Notify {}) => {
-            if cfg!(target_os = "macos") && !args.json {
-                macos_notification(&units).await
+            if cfg!(target_os = "macos") && !json {
+                macos_notification(&units, &client, &options).await
             } else {
-                json_notification(&units, args.verbose).await
+                json_notification(&units, &client, &options, args.verbose).await
             }
         }
+        Some(Commands::Best {
+            location,
+            from,
+            to,
+            duration,
+            top,
+        }) => {
+            best_start_times(
+                location,
+                from,
+                to,
+                *duration,
+                *top,
+                &units,
+                &client,
+                &options.locale,
+                json,
+            )
+            .await
+        }
+        Some(Commands::Pace { name }) => {
+            pace_for(name, &units, &client, &options.locale, json).await
+        }
+        Some(Commands::Check { name }) => {
+            let verdict =
+                check_event(name, &units, &client, &config.check, &options.locale, json).await?;
+            return Ok(verdict.exit_code());
+        }
     }
+    .map(|_| 0)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    if let Err(e) = args.validate() {
+        e.exit();
+    }
+    let code = match run(args).await {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            e.downcast_ref::<wed::Error>().map_or(1, |e| e.exit_code())
+        }
+    };
+    ExitCode::from(u8::try_from(code).unwrap_or(1))
 }
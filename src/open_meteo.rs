@@ -0,0 +1,171 @@
+use chrono::NaiveDateTime;
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::date_format::OPEN_METEO_DATE_FORMAT;
+use crate::error::{Error, Result};
+use crate::http::Client;
+use crate::Event;
+
+/// Open-Meteo responds with a list when the request has more than one location.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Responses<T> {
+    Many(Vec<T>),
+    One(Box<T>),
+}
+
+impl<T> Responses<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            Responses::Many(responses) => responses,
+            Responses::One(response) => vec![*response],
+        }
+    }
+}
+
+/// Returns the unique coordinates of the events, and the index of each event's coordinates.
+pub(crate) fn group_by_coordinates(events: &[Event]) -> (Vec<(f64, f64)>, Vec<usize>) {
+    let mut coordinates: Vec<(f64, f64)> = vec![];
+    let indexes = events
+        .iter()
+        .map(|event| {
+            let pair = (event.latitude, event.longitude);
+            match coordinates.iter().position(|c| *c == pair) {
+                Some(idx) => idx,
+                None => {
+                    coordinates.push(pair);
+                    coordinates.len() - 1
+                }
+            }
+        })
+        .collect();
+    (coordinates, indexes)
+}
+
+fn join(values: impl Iterator<Item = String>) -> String {
+    values.collect::<Vec<String>>().join(",")
+}
+
+/// Query parameters for many locations in a single request.
+pub(crate) fn locations(coordinates: &[(f64, f64)]) -> [(&'static str, String); 2] {
+    [
+        (
+            "latitude",
+            join(coordinates.iter().map(|c| c.0.to_string())),
+        ),
+        (
+            "longitude",
+            join(coordinates.iter().map(|c| c.1.to_string())),
+        ),
+    ]
+}
+
+/// Fetches one response for each of the `locations` in the request, and the time it was fetched
+/// when it comes from a cached response that is not fresh anymore.
+pub(crate) async fn fetch<T: DeserializeOwned>(
+    base: &str,
+    query: &[(&str, String)],
+    locations: usize,
+    client: &Client,
+) -> Result<(Vec<T>, Option<NaiveDateTime>)> {
+    let url = Url::parse_with_params(base, query).map_err(|e| Error::Parse(e.to_string()))?;
+    let (body, as_of) = client.get_cached(&url).await?;
+    let responses = serde_json::from_str::<Responses<T>>(&body)
+        .map_err(|e| {
+            Error::Parse(format!(
+                "Failed to parse response JSON body from {url}: {e}"
+            ))
+        })?
+        .into_vec();
+    if responses.len() != locations {
+        return Err(Error::Parse(format!(
+            "Expected data for {} locations from {}, got {}",
+            locations,
+            url,
+            responses.len()
+        )));
+    }
+    Ok((responses, as_of))
+}
+
+/// Index of the hour closest to the target.
+pub(crate) fn closest(time: &[String], target: NaiveDateTime) -> Option<usize> {
+    time.iter()
+        .enumerate()
+        .filter_map(|(idx, time)| {
+            let time = NaiveDateTime::parse_from_str(time, OPEN_METEO_DATE_FORMAT).ok()?;
+            Some((idx, (target - time).num_minutes().abs()))
+        })
+        .min_by_key(|(_, diff)| *diff)
+        .map(|(idx, _)| idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Response {
+        latitude: f64,
+    }
+
+    fn event(name: &str, when: &str, latitude: f64, longitude: f64) -> Event {
+        Event {
+            name: Some(name.to_string()),
+            when: NaiveDateTime::parse_from_str(when, "%Y-%m-%d %H:%M").unwrap(),
+            location: "Location".to_string(),
+            latitude,
+            longitude,
+            days: 1,
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
+        }
+    }
+
+    #[test]
+    fn test_group_by_coordinates() {
+        let events = vec![
+            event("5K", "2021-05-20 08:00", 45.4, -75.7),
+            event("Gran Fondo", "2021-05-20 07:00", 46.8, -71.2),
+            event("Marathon", "2021-05-21 08:00", 45.4, -75.7),
+        ];
+        let (coordinates, indexes) = group_by_coordinates(&events);
+        assert_eq!(coordinates, vec![(45.4, -75.7), (46.8, -71.2)]);
+        assert_eq!(indexes, vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn test_responses_into_vec() {
+        let one: Responses<Response> = serde_json::from_str(r#"{"latitude": 45.0}"#).unwrap();
+        assert_eq!(one.into_vec(), vec![Response { latitude: 45.0 }]);
+        let many: Responses<Response> =
+            serde_json::from_str(r#"[{"latitude": 45.0}, {"latitude": 46.0}]"#).unwrap();
+        assert_eq!(many.into_vec().len(), 2);
+    }
+
+    #[test]
+    fn test_locations() {
+        assert_eq!(
+            locations(&[(45.0, -75.5), (46.1, -74.0)]),
+            [
+                ("latitude", "45,46.1".to_string()),
+                ("longitude", "-75.5,-74".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_closest() {
+        let time = [
+            "2021-05-20T08:00".to_string(),
+            "2021-05-20T09:00".to_string(),
+        ];
+        let at = |value| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(closest(&time, at("2021-05-20 08:20")), Some(0));
+        assert_eq!(closest(&time, at("2021-05-20 08:40")), Some(1));
+        assert_eq!(closest(&[], at("2021-05-20 08:40")), None);
+    }
+}
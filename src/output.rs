@@ -0,0 +1,420 @@
+use clap::ValueEnum;
+use serde::{self, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+use crate::air;
+use crate::compare::Divergence;
+use crate::date_format::WED_FILE_DATE_FORMAT;
+use crate::error::{Error, Result};
+use crate::gear::{Rules, Sport};
+use crate::i18n::Locale;
+use crate::template::Template;
+use crate::weather::Weather;
+use crate::wind;
+
+/// Optional weather variables shown in the human-readable output (JSON always includes them).
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize)]
+pub enum Field {
+    Precipitation,
+    Gusts,
+    Uv,
+    CloudCover,
+    DewPoint,
+    Visibility,
+    Pressure,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub fields: Vec<Field>,
+
+    /// Sports to recommend gear for, using the gear rules
+    pub sports: Vec<Sport>,
+    pub rules: Rules,
+
+    /// Thresholds above which the air quality gets a warning
+    pub air_quality: air::Thresholds,
+
+    /// Whether to fetch the air quality
+    pub air: bool,
+
+    /// URL of a CAP/Atom feed with severe weather alerts
+    pub alerts: Option<String>,
+
+    /// Whether to fetch the ensemble forecast to show its spread
+    pub ensemble: bool,
+
+    /// Weather models to compare, and the differences above which they are flagged
+    pub models: Vec<String>,
+    pub divergence: Divergence,
+
+    /// Language of the human-readable output
+    pub locale: Locale,
+
+    /// Compass points, arrow and Beaufort force in the human-readable output
+    pub wind: wind::Style,
+
+    /// User-defined template replacing the output format and the notification body
+    pub template: Option<Template>,
+}
+
+impl Options {
+    /// The template when there is one, or the renderer of the format otherwise.
+    pub fn renderer(&self, format: Format) -> Box<dyn Renderer> {
+        match &self.template {
+            Some(template) => Box::new(template.clone()),
+            None => format.renderer(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    /// One human-readable paragraph per event
+    Text,
+    /// The same as `--json`
+    Json,
+    /// Aligned columns, one row per event
+    Table,
+    /// Raw values, for spreadsheets
+    Csv,
+    /// A table in Markdown
+    Markdown,
+    /// The JSON output as YAML
+    Yaml,
+    /// One JSON object per line
+    Ndjson,
+}
+
+impl Format {
+    pub fn renderer(&self) -> Box<dyn Renderer> {
+        match self {
+            Format::Text => Box::new(Text),
+            Format::Json => Box::new(Json),
+            Format::Table => Box::new(Table),
+            Format::Csv => Box::new(Csv),
+            Format::Markdown => Box::new(Markdown),
+            Format::Yaml => Box::new(Yaml),
+            Format::Ndjson => Box::new(Ndjson),
+        }
+    }
+}
+
+/// Turns forecasts into the output of one of the formats.
+pub trait Renderer {
+    fn render(&self, forecasts: &[Weather], options: &Options) -> Result<String>;
+
+    /// Output for a single forecast, which is the same as for a list with only it by default.
+    fn render_one(&self, weather: &Weather, options: &Options) -> Result<String> {
+        self.render(std::slice::from_ref(weather), options)
+    }
+}
+
+pub struct Text;
+pub struct Json;
+pub struct Table;
+pub struct Csv;
+pub struct Markdown;
+pub struct Yaml;
+pub struct Ndjson;
+
+impl Renderer for Text {
+    fn render(&self, forecasts: &[Weather], options: &Options) -> Result<String> {
+        let paragraphs = forecasts
+            .iter()
+            .map(|weather| weather.as_string(options))
+            .collect::<Result<Vec<String>>>()?;
+        Ok(paragraphs.join("\n\n"))
+    }
+}
+
+impl Renderer for Json {
+    fn render(&self, forecasts: &[Weather], _: &Options) -> Result<String> {
+        Ok(serde_json::to_string(forecasts)?)
+    }
+
+    fn render_one(&self, weather: &Weather, _: &Options) -> Result<String> {
+        Ok(serde_json::to_string(weather)?)
+    }
+}
+
+impl Renderer for Yaml {
+    fn render(&self, forecasts: &[Weather], _: &Options) -> Result<String> {
+        Ok(serde_yaml_ng::to_string(forecasts)?.trim_end().to_string())
+    }
+
+    fn render_one(&self, weather: &Weather, _: &Options) -> Result<String> {
+        Ok(serde_yaml_ng::to_string(weather)?.trim_end().to_string())
+    }
+}
+
+impl Renderer for Ndjson {
+    fn render(&self, forecasts: &[Weather], _: &Options) -> Result<String> {
+        let lines = forecasts
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<serde_json::Result<Vec<String>>>()?;
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Header of the human-readable tabular formats.
+const COLUMNS: [&str; 9] = [
+    "event",
+    "location",
+    "date",
+    "weather",
+    "temperature",
+    "feels like",
+    "rain",
+    "humidity",
+    "wind",
+];
+
+/// Cells of the human-readable tabular formats, in the same order as `COLUMNS`.
+fn cells(weather: &Weather, options: &Options) -> [String; 9] {
+    let locale = &options.locale;
+    let temperature = weather.units.temperature.symbol();
+    [
+        weather.name.clone().unwrap_or_default(),
+        weather.location.clone(),
+        locale.date(&weather.date),
+        format!("{} {}", weather.icon, locale.t(weather.description)),
+        format!("{}{}", weather.temperature.round(), temperature),
+        format!("{}{}", weather.feels_like.round(), temperature),
+        format!("{}%", weather.probability_of_precipitation),
+        format!("{}%", weather.humidity),
+        format!(
+            "{}{} {}",
+            weather.wind_speed.round(),
+            weather.units.speed.symbol(),
+            options.wind.as_string(&weather.wind, locale)
+        ),
+    ]
+}
+
+/// Pads the cells so the columns line up, separated by two spaces, counting emojis and other wide
+/// characters as two columns as terminals do.
+pub(crate) fn align<T: AsRef<[String]>>(rows: &[T]) -> Vec<String> {
+    let columns = rows.first().map_or(0, |row| row.as_ref().len());
+    let widths = (0..columns)
+        .map(|idx| {
+            rows.iter()
+                .map(|row| row.as_ref()[idx].width())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<usize>>();
+    rows.iter()
+        .map(|row| {
+            let cells = row
+                .as_ref()
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell}{}", " ".repeat(width - cell.width())))
+                .collect::<Vec<String>>();
+            cells.join("  ").trim_end().to_string()
+        })
+        .collect()
+}
+
+impl Renderer for Table {
+    fn render(&self, forecasts: &[Weather], options: &Options) -> Result<String> {
+        let mut rows = vec![COLUMNS.map(String::from)];
+        rows.extend(forecasts.iter().map(|weather| cells(weather, options)));
+        Ok(align(&rows).join("\n"))
+    }
+}
+
+impl Renderer for Markdown {
+    fn render(&self, forecasts: &[Weather], options: &Options) -> Result<String> {
+        let line = |cells: &[String]| {
+            let cells = cells
+                .iter()
+                .map(|cell| cell.replace('|', "\\|"))
+                .collect::<Vec<String>>();
+            format!("| {} |", cells.join(" | "))
+        };
+        let mut lines = vec![
+            line(&COLUMNS.map(String::from)),
+            line(&COLUMNS.map(|_| "---".to_string())),
+        ];
+        lines.extend(
+            forecasts
+                .iter()
+                .map(|weather| line(&cells(weather, options))),
+        );
+        Ok(lines.join("\n"))
+    }
+}
+
+/// CSV has the raw values, named as in the JSON output, for spreadsheets.
+impl Renderer for Csv {
+    fn render(&self, forecasts: &[Weather], _: &Options) -> Result<String> {
+        let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer.write_record([
+            "name",
+            "location",
+            "date",
+            "weather_code",
+            "description",
+            "temperature",
+            "feels_like",
+            "probability_of_precipitation",
+            "humidity",
+            "wind_speed",
+            "wind_direction",
+            "precipitation",
+            "wind_gusts",
+            "uv_index",
+        ])?;
+        for weather in forecasts {
+            writer.write_record([
+                weather.name.clone().unwrap_or_default(),
+                weather.location.clone(),
+                weather.date.format(WED_FILE_DATE_FORMAT).to_string(),
+                weather.weather_code.to_string(),
+                weather.description.to_string(),
+                weather.temperature.to_string(),
+                weather.feels_like.to_string(),
+                weather.probability_of_precipitation.to_string(),
+                weather.humidity.to_string(),
+                weather.wind_speed.to_string(),
+                weather.wind_direction.to_string(),
+                optional(weather.precipitation),
+                optional(weather.wind_gusts),
+                optional(weather.uv_index),
+            ])?;
+        }
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| Error::Parse(e.to_string()))?;
+        let output = String::from_utf8(bytes).map_err(|e| Error::Parse(e.to_string()))?;
+        Ok(output.trim_end().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stress::Stress;
+    use crate::units::Units;
+    use crate::wind::Wind;
+    use chrono::NaiveDateTime;
+
+    fn weather<'a>(name: &str, temperature: f64) -> Weather<'a> {
+        Weather {
+            name: Some(name.to_string()),
+            location: "Ottawa, CA".to_string(),
+            units: Units::metric(),
+            icon: "☀️",
+            date: NaiveDateTime::parse_from_str("2021-05-20 08:00", "%Y-%m-%d %H:%M").unwrap(),
+            weather_code: 1,
+            description: "mainly clear",
+            probability_of_precipitation: 20,
+            temperature,
+            feels_like: temperature,
+            humidity: 80,
+            wind_speed: 10.0,
+            wind_direction: 180,
+            wind: Wind::new(180, 10.0),
+            precipitation: Some(0.2),
+            wind_gusts: None,
+            uv_index: None,
+            cloud_cover: None,
+            dew_point: None,
+            visibility: None,
+            surface_pressure: None,
+            solar_radiation: None,
+            stress: Stress::new(temperature, 80.0, 10.0, None, &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
+        }
+    }
+
+    fn render(format: Format) -> String {
+        let forecasts = vec![weather("5k", 17.0), weather("Half | Marathon", 21.0)];
+        format
+            .renderer()
+            .render(&forecasts, &Options::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_table() {
+        assert_eq!(
+            render(Format::Table),
+            [
+                "event            location    date           weather          temperature  feels like  rain  humidity  wind",
+                "5k               Ottawa, CA  May 20, 08:00  ☀️ mainly clear  17°C         17°C        20%   80%       10km/h S",
+                "Half | Marathon  Ottawa, CA  May 20, 08:00  ☀️ mainly clear  21°C         21°C        20%   80%       10km/h S",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_align_wide_characters() {
+        let rows = [
+            ["⛅".to_string(), "température".to_string(), "x".to_string()],
+            ["a".to_string(), "t".to_string(), "y".to_string()],
+        ];
+        assert_eq!(
+            align(&rows),
+            vec!["⛅  température  x", "a   t            y"]
+        );
+    }
+
+    #[test]
+    fn test_markdown() {
+        let output = render(Format::Markdown);
+        let lines = output.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[1],
+            "| --- | --- | --- | --- | --- | --- | --- | --- | --- |"
+        );
+        assert!(lines[3].starts_with(r"| Half \| Marathon | Ottawa, CA |"));
+    }
+
+    #[test]
+    fn test_csv() {
+        let output = render(Format::Csv);
+        let lines = output.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("name,location,date,weather_code,"));
+        assert_eq!(
+            lines[1],
+            "5k,\"Ottawa, CA\",2021-05-20 08:00:00,1,mainly clear,17,17,20,80,10,180,0.2,,"
+        );
+    }
+
+    #[test]
+    fn test_json_and_ndjson() {
+        let json = render(Format::Json);
+        assert!(json.starts_with(r#"[{"name":"5k","#));
+        let lines = render(Format::Ndjson);
+        let lines = lines.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with(r#"{"name":"Half | Marathon","#));
+        let one = Format::Json
+            .renderer()
+            .render_one(&weather("5k", 17.0), &Options::default())
+            .unwrap();
+        assert!(one.starts_with(r#"{"name":"5k","#));
+    }
+
+    #[test]
+    fn test_yaml() {
+        let output = render(Format::Yaml);
+        assert!(output.starts_with("- name: 5k\n  location: Ottawa, CA\n"));
+    }
+}
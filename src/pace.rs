@@ -0,0 +1,361 @@
+use serde::{self, Serialize};
+
+use crate::error::{Error, Result};
+use crate::i18n::Locale;
+use crate::units::{round, Temperature, Units};
+use crate::weather::Weather;
+
+/// Slowdown (%) by the sum of temperature and dew point in Fahrenheit, from the heat adjustment
+/// table popularized by Mark Hadley (upper end of each range, interpolated in between).
+const HEAT_TABLE: &[(f64, f64)] = &[
+    (100.0, 0.0),
+    (110.0, 0.5),
+    (120.0, 1.0),
+    (130.0, 2.0),
+    (140.0, 3.0),
+    (150.0, 4.5),
+    (160.0, 6.0),
+    (170.0, 8.0),
+    (180.0, 10.0),
+];
+
+/// Above this sum hard running is not recommended at all.
+const HEAT_LIMIT: f64 = 180.0;
+
+/// Slowdown (%) per km/h of headwind; a tailwind gives back about half of it.
+const HEADWIND_COST: f64 = 0.2;
+const TAILWIND_GAIN: f64 = 0.1;
+
+/// Parses `mm:ss` or `h:mm:ss` into seconds.
+pub fn seconds(value: &str) -> Result<f64> {
+    let error = || Error::Parse(format!("Expected mm:ss or h:mm:ss, got {value}"));
+    let parts = value
+        .split(':')
+        .map(|part| match part.trim().parse::<f64>() {
+            Ok(value) if value.is_finite() && value >= 0.0 => Ok(value),
+            _ => Err(error()),
+        })
+        .collect::<Result<Vec<f64>>>()?;
+    let total = match parts[..] {
+        [minutes, seconds] => minutes * 60.0 + seconds,
+        [hours, minutes, seconds] => hours * 3_600.0 + minutes * 60.0 + seconds,
+        _ => return Err(error()),
+    };
+    if total <= 0.0 {
+        return Err(error());
+    }
+    Ok(total)
+}
+
+/// Formats seconds as `m:ss`, or `h:mm:ss` from one hour on.
+fn duration(seconds: f64) -> String {
+    let total = seconds.round() as i64;
+    let (hours, minutes, seconds) = (total / 3_600, total % 3_600 / 60, total % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// Converts a pace (`mm:ss` per unit of distance, km or mile) to km/h.
+pub fn speed_from_pace(pace: &str, units: &Units) -> Result<f64> {
+    Ok(units.to_km(3_600.0 / seconds(pace)?))
+}
+
+/// Converts a goal speed (per hour in the distance unit, km or mile) to km/h.
+pub fn goal_speed(speed: f64, units: &Units) -> Result<f64> {
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err(Error::Validation(format!(
+            "The goal speed should be above zero, got {speed}"
+        )));
+    }
+    Ok(units.to_km(speed))
+}
+
+/// Dew point in Celsius from the temperature in Celsius and the relative humidity (Magnus
+/// formula), for forecasts without it.
+fn dew_point(temperature: f64, humidity: f64) -> f64 {
+    let (b, c) = (17.625, 243.04);
+    let gamma = (humidity.max(1.0) / 100.0).ln() + b * temperature / (c + temperature);
+    c * gamma / (b - gamma)
+}
+
+fn heat_adjustment(sum: f64) -> f64 {
+    let mut previous = HEAT_TABLE[0];
+    for (limit, adjustment) in HEAT_TABLE {
+        if sum <= *limit {
+            let (low, low_adjustment) = previous;
+            if *limit == low {
+                return *adjustment;
+            }
+            return low_adjustment + (sum - low) / (limit - low) * (adjustment - low_adjustment);
+        }
+        previous = (*limit, *adjustment);
+    }
+    previous.1
+}
+
+fn wind_adjustment(headwind: f64) -> f64 {
+    if headwind >= 0.0 {
+        headwind * HEADWIND_COST
+    } else {
+        headwind * TAILWIND_GAIN
+    }
+}
+
+/// Goal and adjusted paces in seconds per km (or mile), finish times in seconds, and the
+/// adjustments in percent.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Pace {
+    pub name: Option<String>,
+    pub goal_pace: f64,
+    pub adjusted_pace: f64,
+    pub goal_finish: Option<f64>,
+    pub adjusted_finish: Option<f64>,
+    pub temperature_plus_dew_point: f64,
+    pub heat_adjustment: f64,
+    pub headwind: Option<f64>,
+    pub wind_adjustment: f64,
+    pub too_hot: bool,
+}
+
+impl Pace {
+    /// `speed` in km/h and `distance` in km; the headwind is the average over the course legs or
+    /// checkpoints, when the event has any.
+    pub fn new(weather: &Weather, speed: f64, distance: Option<f64>) -> Self {
+        let units = &weather.units;
+        let temperature = units.to_celsius(weather.temperature);
+        let dew_point = match weather.dew_point {
+            Some(value) => units.to_celsius(value),
+            None => dew_point(temperature, weather.humidity.into()),
+        };
+        let sum = Temperature::Fahrenheit.from_celsius(temperature)
+            + Temperature::Fahrenheit.from_celsius(dew_point);
+        let headwinds = if weather.checkpoints.is_empty() {
+            weather
+                .course
+                .iter()
+                .map(|segment| units.to_kmh(segment.headwind))
+                .collect::<Vec<f64>>()
+        } else {
+            weather
+                .checkpoints
+                .iter()
+                .map(|checkpoint| units.to_kmh(checkpoint.wind.headwind))
+                .collect::<Vec<f64>>()
+        };
+        let headwind =
+            (!headwinds.is_empty()).then(|| headwinds.iter().sum::<f64>() / headwinds.len() as f64);
+        let heat = round(heat_adjustment(sum), 1);
+        let wind = round(headwind.map(wind_adjustment).unwrap_or(0.0), 1);
+        let goal_pace = 3_600.0 / units.from_km(speed);
+        let adjusted_pace = goal_pace * (1.0 + (heat + wind) / 100.0);
+        let finish = |pace: f64| distance.map(|km| (units.from_km(km) * pace).round());
+        Self {
+            name: weather.name.clone(),
+            goal_pace: goal_pace.round(),
+            adjusted_pace: adjusted_pace.round(),
+            goal_finish: finish(goal_pace),
+            adjusted_finish: finish(adjusted_pace),
+            temperature_plus_dew_point: sum.round(),
+            heat_adjustment: heat,
+            headwind: headwind.map(|value| round(units.from_kmh(value), 1)),
+            wind_adjustment: wind,
+            too_hot: sum > HEAT_LIMIT,
+        }
+    }
+
+    pub fn as_string(&self, units: &Units, locale: &Locale, json: bool) -> Result<String> {
+        if json {
+            return Ok(serde_json::to_string(self)?);
+        }
+        let (distance, speed) = (units.distance.symbol(), units.speed.symbol());
+        let target = |pace: f64, finish: Option<f64>| match finish {
+            Some(finish) => format!("{}/{} ({})", duration(pace), distance, duration(finish)),
+            None => format!("{}/{}", duration(pace), distance),
+        };
+        let mut reasons = vec![format!(
+            "{:+}% {} ({} {}°F)",
+            self.heat_adjustment,
+            locale.t("for heat"),
+            locale.t("temperature + dew point"),
+            self.temperature_plus_dew_point
+        )];
+        if let Some(headwind) = self.headwind {
+            let label = if headwind < 0.0 {
+                "tailwind"
+            } else {
+                "headwind"
+            };
+            reasons.push(format!(
+                "{:+}% {} {}{} {}",
+                self.wind_adjustment,
+                locale.t("for"),
+                headwind.abs().round(),
+                speed,
+                locale.t(label)
+            ));
+        }
+        let mut output = format!(
+            "{}{} {} → {}: {}",
+            self.name
+                .as_ref()
+                .map(|name| format!("{name}: "))
+                .unwrap_or_default(),
+            locale.t("Goal"),
+            target(self.goal_pace, self.goal_finish),
+            target(self.adjusted_pace, self.adjusted_finish),
+            reasons.join(", ")
+        );
+        if self.too_hot {
+            output.push_str(&format!(" ({})", locale.t("too hot for hard running")));
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::course::Segment;
+    use crate::stress::Stress;
+    use crate::wind::Wind;
+    use chrono::NaiveDateTime;
+
+    fn weather<'a>(temperature: f64, dew_point: Option<f64>, units: Units) -> Weather<'a> {
+        Weather {
+            name: Some("Marathon".to_string()),
+            location: "Location".to_string(),
+            units: units.clone(),
+            icon: "☀️",
+            date: NaiveDateTime::parse_from_str("2021-05-20 07:00", "%Y-%m-%d %H:%M").unwrap(),
+            weather_code: 1,
+            description: "mainly clear",
+            probability_of_precipitation: 0,
+            temperature,
+            feels_like: temperature,
+            humidity: 60,
+            wind_speed: 10.0,
+            wind_direction: 0,
+            wind: Wind::new(0, 10.0),
+            precipitation: None,
+            wind_gusts: None,
+            uv_index: None,
+            cloud_cover: None,
+            dew_point,
+            visibility: None,
+            surface_pressure: None,
+            solar_radiation: None,
+            stress: Stress::new(temperature, 60.0, 10.0, None, &units),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_seconds() {
+        assert_eq!(seconds("5:30").unwrap(), 330.0);
+        assert_eq!(seconds("3:29:59").unwrap(), 12_599.0);
+        assert!(seconds("330").is_err());
+        assert!(seconds("0:00").is_err());
+        assert!(seconds("-5:00").is_err());
+        assert!(seconds("1:-30:00").is_err());
+        assert!(seconds("10:-1").is_err());
+    }
+
+    #[test]
+    fn test_duration() {
+        assert_eq!(duration(330.0), "5:30");
+        assert_eq!(duration(12_599.0), "3:29:59");
+    }
+
+    #[test]
+    fn test_speed_from_pace() {
+        assert_eq!(speed_from_pace("5:00", &Units::metric()).unwrap(), 12.0);
+        assert_eq!(
+            round(speed_from_pace("8:00", &Units::imperial()).unwrap(), 1),
+            12.1
+        );
+        assert!(speed_from_pace("5", &Units::metric()).is_err());
+    }
+
+    #[test]
+    fn test_goal_speed() {
+        assert_eq!(goal_speed(12.0, &Units::metric()).unwrap(), 12.0);
+        assert_eq!(goal_speed(10.0, &Units::imperial()).unwrap(), 16.09344);
+        assert!(goal_speed(0.0, &Units::metric()).is_err());
+        assert!(goal_speed(-5.0, &Units::metric()).is_err());
+        assert!(goal_speed(f64::INFINITY, &Units::metric()).is_err());
+        assert!(goal_speed(f64::NAN, &Units::metric()).is_err());
+    }
+
+    #[test]
+    fn test_dew_point() {
+        assert_eq!(round(dew_point(25.0, 60.0), 1), 16.7);
+    }
+
+    #[test]
+    fn test_heat_adjustment() {
+        assert_eq!(heat_adjustment(90.0), 0.0);
+        assert_eq!(heat_adjustment(125.0), 1.5);
+        assert_eq!(heat_adjustment(150.0), 4.5);
+        assert_eq!(heat_adjustment(200.0), 10.0);
+    }
+
+    #[test]
+    fn test_pace_in_the_heat() {
+        // 77°F + 59°F = 136
+        let pace = Pace::new(
+            &weather(25.0, Some(15.0), Units::metric()),
+            12.0,
+            Some(10.0),
+        );
+        assert_eq!(pace.temperature_plus_dew_point, 136.0);
+        assert_eq!(pace.heat_adjustment, 2.6);
+        assert_eq!(pace.wind_adjustment, 0.0);
+        assert_eq!(pace.goal_pace, 300.0);
+        assert_eq!(pace.adjusted_pace, 308.0);
+        assert_eq!(pace.goal_finish, Some(3_000.0));
+        assert_eq!(pace.adjusted_finish, Some(3_078.0));
+        assert!(!pace.too_hot);
+    }
+
+    #[test]
+    fn test_pace_with_headwind() {
+        let mut weather = weather(50.0, Some(40.0), Units::imperial());
+        weather.course = vec![
+            Segment::new(0.0, 10.0, 0, &weather.units),
+            Segment::new(90.0, 10.0, 0, &weather.units),
+        ];
+        let pace = Pace::new(&weather, 12.0, None);
+        assert_eq!(pace.heat_adjustment, 0.0);
+        assert_eq!(pace.headwind, Some(5.0));
+        assert_eq!(pace.wind_adjustment, 1.6);
+        assert_eq!(pace.goal_finish, None);
+    }
+
+    #[test]
+    fn test_pace_as_string() {
+        let pace = Pace::new(
+            &weather(25.0, Some(15.0), Units::metric()),
+            12.0,
+            Some(10.0),
+        );
+        assert_eq!(
+            pace.as_string(&Units::metric(), &Locale::En, false).unwrap(),
+            "Marathon: Goal 5:00/km (50:00) → 5:08/km (51:18): +2.6% for heat (temperature + dew point 136°F)"
+        );
+        assert_eq!(
+            pace.as_string(&Units::metric(), &Locale::De, false).unwrap(),
+            "Marathon: Ziel 5:00/km (50:00) → 5:08/km (51:18): +2.6% für Hitze (Temperatur + Taupunkt 136°F)"
+        );
+    }
+}
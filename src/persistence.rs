@@ -1,9 +1,10 @@
 use std::{fs::File, io::BufReader, path::PathBuf};
 
-use anyhow::{anyhow, Result};
 use chrono::{Local, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
+use crate::course::Waypoint;
+use crate::error::{Error, Result};
 use crate::{date_format, Event};
 
 const OLD_FILE_NAME: &str = ".wed";
@@ -18,6 +19,20 @@ pub struct SavedEvent {
 
     #[serde(with = "date_format")]
     pub when: NaiveDateTime,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bearings: Vec<f64>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub course: Vec<Waypoint>,
+
+    /// Target speed in km/h, to estimate the arrival time along the course and adjust the pace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f64>,
+
+    /// Distance in km, to estimate the finish time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f64>,
 }
 
 impl SavedEvent {
@@ -29,13 +44,21 @@ impl SavedEvent {
             latitude: self.latitude,
             longitude: self.longitude,
             days: (self.when - chrono::Local::now().naive_local()).num_days(),
+            bearings: self.bearings.clone(),
+            course: self.course.clone(),
+            speed: self.speed,
+            distance: self.distance,
         }
     }
 
     pub fn from_event(event: &Event) -> Result<Self> {
         let name = match &event.name {
             Some(name) => name.clone(),
-            None => return Err(anyhow!("Cannot create an event without a name")),
+            None => {
+                return Err(Error::Validation(
+                    "Cannot create an event without a name".to_string(),
+                ))
+            }
         };
 
         Ok(Self {
@@ -44,6 +67,10 @@ impl SavedEvent {
             latitude: event.latitude,
             longitude: event.longitude,
             when: event.when,
+            bearings: event.bearings.clone(),
+            course: event.course.clone(),
+            speed: event.speed,
+            distance: event.distance,
         })
     }
 }
@@ -54,7 +81,8 @@ pub struct SavedEvents {
 }
 
 fn default_storage_path() -> Result<PathBuf> {
-    let mut path = home::home_dir().ok_or(anyhow!("Couldn't find home directory"))?;
+    let mut path =
+        home::home_dir().ok_or(Error::Storage("Couldn't find home directory".to_string()))?;
     path.push(FILE_NAME);
     Ok(path)
 }
@@ -104,6 +132,10 @@ impl SavedEvents {
         self.to_file_path(&default_storage_path()?)
     }
 
+    pub fn get(&self, name: &str) -> Option<&SavedEvent> {
+        self.events.iter().find(|event| event.name == name)
+    }
+
     pub fn add(&mut self, event: SavedEvent) {
         if self.events.contains(&event) {
             return;
@@ -114,7 +146,7 @@ impl SavedEvents {
     fn cleanup(&mut self) {
         let now = Local::now().naive_local();
         self.events.retain(|event| event.when > now);
-        self.events.sort_by(|a, b| a.when.cmp(&b.when));
+        self.events.sort_by_key(|event| event.when);
     }
 }
 
@@ -151,6 +183,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when: NaiveDateTime::parse_from_str("2021-05-20 8:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
+            bearings: vec![90.0, 270.0],
+            course: vec![],
+            speed: None,
+            distance: None,
         };
         let current_time = Local::now().naive_local();
         let expected_event = Event {
@@ -160,6 +196,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             days: (saved_event.when - current_time).num_days(),
+            bearings: vec![90.0, 270.0],
+            course: vec![],
+            speed: None,
+            distance: None,
         };
 
         assert_eq!(saved_event.to_event(), expected_event);
@@ -174,6 +214,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             days: 10,
+            bearings: vec![45.0],
+            course: vec![],
+            speed: None,
+            distance: None,
         };
         let expected_saved_event = SavedEvent {
             name: "Event Name".to_string(),
@@ -181,6 +225,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when: NaiveDateTime::parse_from_str("2021-05-20 8:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
+            bearings: vec![45.0],
+            course: vec![],
+            speed: None,
+            distance: None,
         };
         let result = SavedEvent::from_event(&event);
         assert!(result.is_ok());
@@ -198,6 +246,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             days: 10,
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         };
 
         let result = SavedEvent::from_event(&event);
@@ -218,6 +270,10 @@ mod tests {
                 latitude: 42.0,
                 longitude: -73.0,
                 when: Local::now().naive_local() + Duration::try_days(1).unwrap(),
+                bearings: vec![],
+                course: vec![],
+                speed: None,
+                distance: None,
             },
             SavedEvent {
                 name: "Event 2".to_string(),
@@ -225,6 +281,10 @@ mod tests {
                 latitude: 42.0,
                 longitude: -73.0,
                 when: Local::now().naive_local() + Duration::try_days(2).unwrap(),
+                bearings: vec![],
+                course: vec![],
+                speed: None,
+                distance: None,
             },
         ];
         serde_json::to_writer(File::create(&path).unwrap(), &SavedEvents { events }).unwrap();
@@ -247,6 +307,10 @@ mod tests {
                 latitude: 42.0,
                 longitude: -73.0,
                 when: Local::now().naive_local() - Duration::try_days(1).unwrap(),
+                bearings: vec![],
+                course: vec![],
+                speed: None,
+                distance: None,
             },
             SavedEvent {
                 name: "Event 2".to_string(),
@@ -254,6 +318,10 @@ mod tests {
                 latitude: 42.0,
                 longitude: -73.0,
                 when: Local::now().naive_local() + Duration::try_days(2).unwrap(),
+                bearings: vec![],
+                course: vec![],
+                speed: None,
+                distance: None,
             },
         ];
         serde_json::to_writer(File::create(&path).unwrap(), &SavedEvents { events }).unwrap();
@@ -292,6 +360,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when: Local::now().naive_local() + Duration::try_days(1).unwrap(),
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         });
         saved_events.add(SavedEvent {
             name: "Event 2".to_string(),
@@ -299,6 +371,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when: Local::now().naive_local() + Duration::try_days(2).unwrap(),
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         });
         saved_events.to_file_path(&path).unwrap();
         let loaded = SavedEvents::from_file_path(&path).unwrap();
@@ -319,6 +395,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when: Local::now().naive_local() - Duration::try_days(1).unwrap(),
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         });
         saved_events.add(SavedEvent {
             name: "Event 2".to_string(),
@@ -326,6 +406,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when: Local::now().naive_local() + Duration::try_days(2).unwrap(),
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         });
         saved_events.to_file_path(&path).unwrap();
         let loaded = SavedEvents::from_file_path(&path).unwrap();
@@ -345,6 +429,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when,
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         });
         saved_events.add(SavedEvent {
             name: "Event 2".to_string(),
@@ -352,6 +440,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when: Local::now().naive_local() + Duration::try_days(2).unwrap(),
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         });
         saved_events.add(SavedEvent {
             name: "Event 1".to_string(),
@@ -359,11 +451,34 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when,
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         });
 
         assert_eq!(saved_events.events.len(), 2);
     }
 
+    #[test]
+    fn test_saved_events_get() {
+        let mut saved_events = SavedEvents::new();
+        saved_events.add(SavedEvent {
+            name: "Event 1".to_string(),
+            location: "Location 1".to_string(),
+            latitude: 42.0,
+            longitude: -73.0,
+            when: Local::now().naive_local() + Duration::try_days(1).unwrap(),
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
+        });
+
+        assert_eq!(saved_events.get("Event 1").unwrap().location, "Location 1");
+        assert!(saved_events.get("Event 2").is_none());
+    }
+
     #[test]
     fn test_saved_events_cleanup() {
         let mut saved_events = SavedEvents::new();
@@ -374,6 +489,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when: Local::now().naive_local() - Duration::try_days(1).unwrap(),
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         });
         saved_events.add(SavedEvent {
             name: "Event 2".to_string(),
@@ -381,6 +500,10 @@ mod tests {
             latitude: 42.0,
             longitude: -73.0,
             when: Local::now().naive_local() + Duration::try_days(2).unwrap(),
+            bearings: vec![],
+            course: vec![],
+            speed: None,
+            distance: None,
         });
 
         saved_events.cleanup();
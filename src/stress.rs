@@ -0,0 +1,208 @@
+use serde::{self, Serialize};
+
+use crate::units::{round, Units};
+
+/// Risk flags as used by race directors (e.g. ACSM's WBGT flags for endurance events).
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Risk {
+    Green,
+    Yellow,
+    Red,
+    Black,
+}
+
+impl Risk {
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            Risk::Green => "🟢",
+            Risk::Yellow => "🟡",
+            Risk::Red => "🔴",
+            Risk::Black => "⚫",
+        }
+    }
+}
+
+/// Heat and cold stress indices, in the same temperature unit as the forecast. Heat index and
+/// wind chill are only present when the conditions they are defined for apply.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Stress {
+    pub wbgt: f64,
+    pub wbgt_risk: Risk,
+    pub heat_index: Option<f64>,
+    pub heat_index_risk: Risk,
+    pub wind_chill: Option<f64>,
+    pub wind_chill_risk: Risk,
+}
+
+/// Psychrometric wet-bulb temperature (°C) from Stull (2011).
+fn wet_bulb(temperature: f64, humidity: f64) -> f64 {
+    temperature * (0.151977 * (humidity + 8.313659).sqrt()).atan() + (temperature + humidity).atan()
+        - (humidity - 1.676331).atan()
+        + 0.00391838 * humidity.powf(1.5) * (0.023101 * humidity).atan()
+        - 4.686035
+}
+
+/// Black globe temperature (°C) estimated from the heat a 150mm globe absorbs from solar
+/// radiation (W/m²) and loses by convection, given the wind speed (km/h).
+fn globe(temperature: f64, wind_speed: f64, radiation: f64) -> f64 {
+    let wind = (wind_speed / 3.6).max(0.5);
+    let coefficient = 6.3 * wind.powf(0.6) + 3.9;
+    temperature + 0.95 * 0.25 * radiation / coefficient
+}
+
+/// Outdoor Wet Bulb Globe Temperature estimate (°C).
+fn wbgt(temperature: f64, humidity: f64, wind_speed: f64, radiation: f64) -> f64 {
+    0.7 * wet_bulb(temperature, humidity)
+        + 0.2 * globe(temperature, wind_speed, radiation)
+        + 0.1 * temperature
+}
+
+/// Heat index (°C) from the NWS Rothfusz regression, defined from 27°C (80°F).
+fn heat_index(temperature: f64, humidity: f64) -> Option<f64> {
+    let t = temperature * 9.0 / 5.0 + 32.0;
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + humidity * 0.094);
+    let value = if (simple + t) / 2.0 < 80.0 {
+        simple
+    } else {
+        -42.379 + 2.04901523 * t + 10.14333127 * humidity
+            - 0.22475541 * t * humidity
+            - 0.00683783 * t * t
+            - 0.05481717 * humidity * humidity
+            + 0.00122874 * t * t * humidity
+            + 0.00085282 * t * humidity * humidity
+            - 0.00000199 * t * t * humidity * humidity
+    };
+    if value < 80.0 {
+        return None;
+    }
+    Some((value - 32.0) * 5.0 / 9.0)
+}
+
+/// Wind chill (°C) as used by Environment Canada and the NWS, defined up to 10°C and from
+/// 4.8km/h.
+fn wind_chill(temperature: f64, wind_speed: f64) -> Option<f64> {
+    if temperature > 10.0 || wind_speed < 4.8 {
+        return None;
+    }
+    let wind = wind_speed.powf(0.16);
+    Some(13.12 + 0.6215 * temperature - 11.37 * wind + 0.3965 * temperature * wind)
+}
+
+fn wbgt_risk(value: f64) -> Risk {
+    match value {
+        v if v < 18.0 => Risk::Green,
+        v if v < 23.0 => Risk::Yellow,
+        v if v < 28.0 => Risk::Red,
+        _ => Risk::Black,
+    }
+}
+
+fn heat_index_risk(value: Option<f64>) -> Risk {
+    match value {
+        None => Risk::Green,
+        Some(v) if v < 39.0 => Risk::Yellow,
+        Some(v) if v < 51.0 => Risk::Red,
+        Some(_) => Risk::Black,
+    }
+}
+
+fn wind_chill_risk(value: Option<f64>) -> Risk {
+    match value {
+        Some(v) if v <= -40.0 => Risk::Black,
+        Some(v) if v <= -28.0 => Risk::Red,
+        Some(v) if v <= -10.0 => Risk::Yellow,
+        _ => Risk::Green,
+    }
+}
+
+impl Stress {
+    /// Takes the temperature and wind speed in the given units, humidity in % and solar radiation
+    /// in W/m² (no radiation is assumed when unknown).
+    pub fn new(
+        temperature: f64,
+        humidity: f64,
+        wind_speed: f64,
+        radiation: Option<f64>,
+        units: &Units,
+    ) -> Self {
+        let temperature = units.to_celsius(temperature);
+        let wind_speed = units.to_kmh(wind_speed);
+        let wbgt = wbgt(temperature, humidity, wind_speed, radiation.unwrap_or(0.0));
+        let heat_index = heat_index(temperature, humidity);
+        let wind_chill = wind_chill(temperature, wind_speed);
+        Self {
+            wbgt: round(units.from_celsius(wbgt), 1),
+            wbgt_risk: wbgt_risk(wbgt),
+            heat_index: heat_index.map(|v| round(units.from_celsius(v), 1)),
+            heat_index_risk: heat_index_risk(heat_index),
+            wind_chill: wind_chill.map(|v| round(units.from_celsius(v), 1)),
+            wind_chill_risk: wind_chill_risk(wind_chill),
+        }
+    }
+
+    pub fn risk(&self) -> Risk {
+        self.wbgt_risk
+            .max(self.heat_index_risk)
+            .max(self.wind_chill_risk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wet_bulb() {
+        // reference value from Stull (2011)
+        assert!((wet_bulb(20.0, 50.0) - 13.7).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_heat_index() {
+        assert_eq!(heat_index(20.0, 50.0), None);
+        // 90°F and 60% humidity is 100°F on the NWS chart
+        let value = heat_index(32.2, 60.0).unwrap();
+        assert!((value - 37.8).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_wind_chill() {
+        assert_eq!(wind_chill(15.0, 20.0), None);
+        assert_eq!(wind_chill(-5.0, 2.0), None);
+        // -20°C and 30km/h is -33 on Environment Canada's chart
+        let value = wind_chill(-20.0, 30.0).unwrap();
+        assert!((value - -32.6).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_stress_mild_morning() {
+        let stress = Stress::new(12.0, 70.0, 10.0, Some(100.0), &Units::metric());
+        assert_eq!(stress.risk(), Risk::Green);
+        assert_eq!(stress.heat_index, None);
+        assert_eq!(stress.wind_chill, None);
+    }
+
+    #[test]
+    fn test_stress_hot_and_sunny() {
+        let stress = Stress::new(32.0, 60.0, 5.0, Some(800.0), &Units::metric());
+        assert_eq!(stress.wbgt_risk, Risk::Black);
+        assert!(stress.heat_index.is_some());
+        assert_eq!(stress.risk(), Risk::Black);
+    }
+
+    #[test]
+    fn test_stress_cold_and_windy() {
+        let stress = Stress::new(-15.0, 70.0, 30.0, None, &Units::metric());
+        assert_eq!(stress.wind_chill_risk, Risk::Yellow);
+        assert_eq!(stress.risk(), Risk::Yellow);
+    }
+
+    #[test]
+    fn test_stress_in_imperial_units() {
+        let metric = Stress::new(30.0, 50.0, 16.09344, Some(500.0), &Units::metric());
+        let imperial = Stress::new(86.0, 50.0, 10.0, Some(500.0), &Units::imperial());
+        assert_eq!(metric.wbgt_risk, imperial.wbgt_risk);
+        assert!((imperial.wbgt - (metric.wbgt * 9.0 / 5.0 + 32.0)).abs() < 0.2);
+    }
+}
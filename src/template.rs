@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDateTime;
+use minijinja::{context, Environment, Value};
+
+use crate::date_format::WED_FILE_DATE_FORMAT;
+use crate::error::Result;
+use crate::output::{Options, Renderer};
+use crate::weather::Weather;
+
+const NAME: &str = "template";
+
+/// User-defined output, using Jinja syntax over the fields of the JSON output, e.g.
+/// `{{ icon }} {{ temperature | round | int }}{{ symbols.temperature }}`.
+#[derive(Debug, Clone)]
+pub struct Template {
+    source: String,
+}
+
+/// Formats a date from the JSON output with a strftime format, e.g. `{{ date | strftime("%a %H:%M") }}`.
+fn strftime(value: String, format: String) -> std::result::Result<String, minijinja::Error> {
+    let invalid =
+        |detail: String| minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, detail);
+    let date = NaiveDateTime::parse_from_str(&value, WED_FILE_DATE_FORMAT)
+        .map_err(|e| invalid(e.to_string()))?;
+    // chrono panics when displaying a date with an invalid format, so it is checked beforehand
+    let items = StrftimeItems::new(&format).collect::<Vec<Item>>();
+    if items.contains(&Item::Error) {
+        return Err(invalid(format!("Invalid strftime format: {format}")));
+    }
+    Ok(date.format_with_items(items.into_iter()).to_string())
+}
+
+impl Template {
+    /// Uses the template with this name from the config, or takes it as the template itself.
+    pub fn new(template: &str, named: &HashMap<String, String>) -> Self {
+        Self {
+            source: named
+                .get(template)
+                .cloned()
+                .unwrap_or_else(|| template.to_string()),
+        }
+    }
+
+    fn environment(&self) -> Result<Environment<'_>> {
+        let mut environment = Environment::new();
+        environment.add_filter("strftime", strftime);
+        environment.add_template(NAME, &self.source)?;
+        Ok(environment)
+    }
+}
+
+impl Renderer for Template {
+    fn render(&self, forecasts: &[Weather], _: &Options) -> Result<String> {
+        let environment = self.environment()?;
+        let template = environment.get_template(NAME)?;
+        let lines = forecasts
+            .iter()
+            .map(|weather| {
+                let symbols = context! {
+                    temperature => weather.units.temperature.symbol(),
+                    speed => weather.units.speed.symbol(),
+                    precipitation => weather.units.precipitation.symbol(),
+                    distance => weather.units.distance.symbol(),
+                };
+                template.render(context! { symbols, ..Value::from_serialize(weather) })
+            })
+            .collect::<std::result::Result<Vec<String>, minijinja::Error>>()?;
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stress::Stress;
+    use crate::units::Units;
+    use crate::wind::Wind;
+
+    fn weather<'a>() -> Weather<'a> {
+        Weather {
+            name: Some("Event".to_string()),
+            location: "Ottawa, CA".to_string(),
+            units: Units::metric(),
+            icon: "☀️",
+            date: NaiveDateTime::parse_from_str("2021-05-20 08:00", "%Y-%m-%d %H:%M").unwrap(),
+            weather_code: 1,
+            description: "mainly clear",
+            probability_of_precipitation: 20,
+            temperature: 17.4,
+            feels_like: 16.6,
+            humidity: 80,
+            wind_speed: 10.0,
+            wind_direction: 200,
+            wind: Wind::new(200, 10.0),
+            precipitation: None,
+            wind_gusts: None,
+            uv_index: None,
+            cloud_cover: None,
+            dew_point: None,
+            visibility: None,
+            surface_pressure: None,
+            solar_radiation: None,
+            stress: Stress::new(17.4, 80.0, 10.0, None, &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_template_new() {
+        let named = HashMap::from([("tmux".to_string(), "{{ icon }}".to_string())]);
+        assert_eq!(Template::new("tmux", &named).source, "{{ icon }}");
+        assert_eq!(Template::new("{{ name }}", &named).source, "{{ name }}");
+    }
+
+    #[test]
+    fn test_template_render() {
+        let template = Template::new(
+            "{{ name }} {{ date | strftime(\"%a %H:%M\") }}: {{ icon }} {{ temperature | round | int }}{{ symbols.temperature }} (feels like {{ feels_like | round | int }}) {{ wind.compass_16 }} {{ wind_speed }}{{ symbols.speed }}",
+            &HashMap::new(),
+        );
+        assert_eq!(
+            template
+                .render(&[weather(), weather()], &Options::default())
+                .unwrap(),
+            ["Event Thu 08:00: ☀️ 17°C (feels like 17) SSW 10.0km/h"; 2].join("\n")
+        );
+    }
+
+    #[test]
+    fn test_template_invalid_strftime() {
+        let template = Template::new("{{ date | strftime(\"%Q\") }}", &HashMap::new());
+        assert!(template.render(&[weather()], &Options::default()).is_err());
+    }
+
+    #[test]
+    fn test_template_invalid() {
+        let template = Template::new("{{ name ", &HashMap::new());
+        assert!(template.render(&[weather()], &Options::default()).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::error::{Error, Result};
+use tokio::sync::Mutex;
+
+// serializes throttled requests within the process, while the timestamp file takes care of
+// successive runs of the binary
+static LOCK: Mutex<()> = Mutex::const_new(());
+
+pub struct Throttle {
+    path: PathBuf,
+    interval: Duration,
+}
+
+impl Throttle {
+    pub fn new(path: PathBuf, interval: Duration) -> Self {
+        Self { path, interval }
+    }
+
+    pub fn in_home_dir(name: &str, interval: Duration) -> Result<Self> {
+        let mut path =
+            home::home_dir().ok_or(Error::Storage("Couldn't find home directory".to_string()))?;
+        path.push(name);
+        Ok(Self::new(path, interval))
+    }
+
+    fn last_request(&self) -> Option<SystemTime> {
+        let contents = fs::read_to_string(&self.path).ok()?;
+        let millis = contents.trim().parse::<u64>().ok()?;
+        Some(UNIX_EPOCH + Duration::from_millis(millis))
+    }
+
+    fn delay(&self, last: Option<SystemTime>, now: SystemTime) -> Option<Duration> {
+        let elapsed = now.duration_since(last?).unwrap_or_default();
+        self.interval.checked_sub(elapsed).filter(|d| !d.is_zero())
+    }
+
+    fn record(&self, when: SystemTime) -> Result<()> {
+        let millis = when
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| Error::Storage(e.to_string()))?
+            .as_millis();
+        fs::write(&self.path, millis.to_string())?;
+        Ok(())
+    }
+
+    /// Waits until the interval since the last request (from this or a previous run) has passed,
+    /// and records the current time as the moment of the next request.
+    pub async fn wait(&self) -> Result<()> {
+        let _guard = LOCK.lock().await;
+        if let Some(delay) = self.delay(self.last_request(), SystemTime::now()) {
+            tokio::time::sleep(delay).await;
+        }
+        self.record(SystemTime::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    fn create_throttle() -> (Throttle, TempDir) {
+        let tmp = TempDir::new("wed-test-throttle").unwrap();
+        let throttle = Throttle::new(tmp.path().join(".wed.nominatim"), Duration::from_secs(1));
+        (throttle, tmp)
+    }
+
+    #[test]
+    fn test_throttle_delay_without_previous_request() {
+        let (throttle, tmp) = create_throttle();
+        assert_eq!(throttle.delay(None, SystemTime::now()), None);
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn test_throttle_delay_with_recent_request() {
+        let (throttle, tmp) = create_throttle();
+        let now = SystemTime::now();
+        let last = now - Duration::from_millis(400);
+        assert_eq!(
+            throttle.delay(Some(last), now),
+            Some(Duration::from_millis(600))
+        );
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn test_throttle_delay_with_old_request() {
+        let (throttle, tmp) = create_throttle();
+        let now = SystemTime::now();
+        let last = now - Duration::from_secs(2);
+        assert_eq!(throttle.delay(Some(last), now), None);
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn test_throttle_persists_last_request() {
+        let (throttle, tmp) = create_throttle();
+        let when = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+        throttle.record(when).unwrap();
+        assert_eq!(throttle.last_request(), Some(when));
+        tmp.close().unwrap();
+    }
+}
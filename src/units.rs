@@ -1,27 +1,279 @@
 use clap::ValueEnum;
 use serde::{self, Serialize};
 
-#[derive(Debug, Clone, ValueEnum, Serialize)]
-pub enum Units {
+use crate::wind;
+
+const KM_PER_MILE: f64 = 1.609344;
+const KMH_PER_KNOT: f64 = 1.852;
+const MM_PER_INCH: f64 = 25.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Temperature {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl Temperature {
+    /// Converts a temperature in this unit to Celsius.
+    pub fn to_celsius(&self, value: f64) -> f64 {
+        match self {
+            Temperature::Celsius => value,
+            Temperature::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+            Temperature::Kelvin => value - 273.15,
+        }
+    }
+
+    /// Converts a temperature in Celsius to this unit.
+    pub fn from_celsius(&self, value: f64) -> f64 {
+        match self {
+            Temperature::Celsius => value,
+            Temperature::Fahrenheit => value * 9.0 / 5.0 + 32.0,
+            Temperature::Kelvin => value + 273.15,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Temperature::Celsius => "°C",
+            Temperature::Fahrenheit => "°F",
+            Temperature::Kelvin => "K",
+        }
+    }
+}
+
+/// Beaufort is kept as a fractional force number, interpolated in the same table as the force
+/// descriptions, so it converts back and forth without losses; negative values (e.g. a tailwind)
+/// keep their sign.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Speed {
+    Kmh,
+    Mph,
+    Ms,
+    Knots,
+    Beaufort,
+}
+
+impl Speed {
+    /// Converts a speed in this unit to km/h.
+    pub fn to_kmh(&self, value: f64) -> f64 {
+        match self {
+            Speed::Kmh => value,
+            Speed::Mph => value * KM_PER_MILE,
+            Speed::Ms => value * 3.6,
+            Speed::Knots => value * KMH_PER_KNOT,
+            Speed::Beaufort => wind::kmh_from_force(value),
+        }
+    }
+
+    /// Converts a speed in km/h to this unit.
+    pub fn from_kmh(&self, value: f64) -> f64 {
+        match self {
+            Speed::Kmh => value,
+            Speed::Mph => value / KM_PER_MILE,
+            Speed::Ms => value / 3.6,
+            Speed::Knots => value / KMH_PER_KNOT,
+            Speed::Beaufort => wind::force_from_kmh(value),
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Speed::Kmh => "km/h",
+            Speed::Mph => "mph",
+            Speed::Ms => "m/s",
+            Speed::Knots => "kn",
+            Speed::Beaufort => "Bft",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precipitation {
+    Mm,
+    Inch,
+}
+
+impl Precipitation {
+    /// Converts a precipitation amount in this unit to millimetres.
+    pub fn to_mm(&self, value: f64) -> f64 {
+        match self {
+            Precipitation::Mm => value,
+            Precipitation::Inch => value * MM_PER_INCH,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Precipitation::Mm => "mm",
+            Precipitation::Inch => "in",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Distance {
+    Km,
+    Mi,
+}
+
+impl Distance {
+    /// Converts a distance in this unit to km.
+    pub fn to_km(&self, value: f64) -> f64 {
+        match self {
+            Distance::Km => value,
+            Distance::Mi => value * KM_PER_MILE,
+        }
+    }
+
+    /// Converts a distance in km to this unit.
+    pub fn from_km(&self, value: f64) -> f64 {
+        match self {
+            Distance::Km => value,
+            Distance::Mi => value / KM_PER_MILE,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Distance::Km => "km",
+            Distance::Mi => "mi",
+        }
+    }
+}
+
+/// Rounds to the number of decimals, for values that are shown or serialized.
+pub fn round(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+/// Presets for all the units at once.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Preset {
     Metric,
     Imperial,
 }
 
+/// Units for each quantity; goal speeds are per hour in the distance unit (km/h or mph).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Units {
+    pub temperature: Temperature,
+    pub speed: Speed,
+    pub precipitation: Precipitation,
+    pub distance: Distance,
+}
+
+impl From<Preset> for Units {
+    fn from(preset: Preset) -> Self {
+        match preset {
+            Preset::Metric => Self::metric(),
+            Preset::Imperial => Self::imperial(),
+        }
+    }
+}
+
 impl Units {
+    pub fn metric() -> Self {
+        Self {
+            temperature: Temperature::Celsius,
+            speed: Speed::Kmh,
+            precipitation: Precipitation::Mm,
+            distance: Distance::Km,
+        }
+    }
+
+    pub fn imperial() -> Self {
+        Self {
+            temperature: Temperature::Fahrenheit,
+            speed: Speed::Mph,
+            precipitation: Precipitation::Inch,
+            distance: Distance::Mi,
+        }
+    }
+
+    /// Open-Meteo's `temperature_unit`, Kelvin is converted from Celsius after the request.
     pub fn temperature(&self) -> String {
-        match self {
-            Units::Metric => "celsius",
-            Units::Imperial => "fahrenheit",
+        match self.temperature {
+            Temperature::Fahrenheit => "fahrenheit",
+            Temperature::Celsius | Temperature::Kelvin => "celsius",
         }
         .to_string()
     }
+
+    /// Open-Meteo's `wind_speed_unit`, Beaufort is converted from km/h after the request.
     pub fn speed(&self) -> String {
-        match self {
-            Units::Metric => "kmh",
-            Units::Imperial => "mph",
+        match self.speed {
+            Speed::Mph => "mph",
+            Speed::Ms => "ms",
+            Speed::Knots => "kn",
+            Speed::Kmh | Speed::Beaufort => "kmh",
+        }
+        .to_string()
+    }
+
+    pub fn precipitation(&self) -> String {
+        match self.precipitation {
+            Precipitation::Mm => "mm",
+            Precipitation::Inch => "inch",
         }
         .to_string()
     }
+
+    /// Converts a temperature from the unit of the request to these units.
+    pub fn from_response_temperature(&self, value: f64) -> f64 {
+        match self.temperature {
+            Temperature::Kelvin => Temperature::Kelvin.from_celsius(value),
+            _ => value,
+        }
+    }
+
+    /// Converts a speed from the unit of the request to these units.
+    pub fn from_response_speed(&self, value: f64) -> f64 {
+        match self.speed {
+            Speed::Beaufort => Speed::Beaufort.from_kmh(value),
+            _ => value,
+        }
+    }
+
+    /// Converts a temperature in these units to Celsius.
+    pub fn to_celsius(&self, value: f64) -> f64 {
+        self.temperature.to_celsius(value)
+    }
+
+    /// Converts a temperature in Celsius to these units.
+    pub fn from_celsius(&self, value: f64) -> f64 {
+        self.temperature.from_celsius(value)
+    }
+
+    /// Converts a precipitation amount in these units to millimetres.
+    pub fn to_mm(&self, value: f64) -> f64 {
+        self.precipitation.to_mm(value)
+    }
+
+    /// Converts a distance in these units to km.
+    pub fn to_km(&self, value: f64) -> f64 {
+        self.distance.to_km(value)
+    }
+
+    /// Converts a distance in km to these units.
+    pub fn from_km(&self, value: f64) -> f64 {
+        self.distance.from_km(value)
+    }
+
+    /// Converts a speed in these units to km/h.
+    pub fn to_kmh(&self, value: f64) -> f64 {
+        self.speed.to_kmh(value)
+    }
+
+    /// Converts a speed in km/h to these units.
+    pub fn from_kmh(&self, value: f64) -> f64 {
+        self.speed.from_kmh(value)
+    }
 }
 
 #[cfg(test)]
@@ -30,25 +282,124 @@ mod tests {
 
     #[test]
     fn test_units_temperature_metric() {
-        let units = Units::Metric;
+        let units = Units::metric();
         assert_eq!(units.temperature(), "celsius");
     }
 
     #[test]
     fn test_units_temperature_imperial() {
-        let units = Units::Imperial;
+        let units = Units::imperial();
         assert_eq!(units.temperature(), "fahrenheit");
     }
 
+    #[test]
+    fn test_units_temperature_kelvin() {
+        let units = Units {
+            temperature: Temperature::Kelvin,
+            ..Units::metric()
+        };
+        assert_eq!(units.temperature(), "celsius");
+        assert_eq!(units.from_response_temperature(20.0), 293.15);
+    }
+
     #[test]
     fn test_units_speed_metric() {
-        let units = Units::Metric;
+        let units = Units::metric();
         assert_eq!(units.speed(), "kmh");
     }
 
     #[test]
     fn test_units_speed_imperial() {
-        let units = Units::Imperial;
+        let units = Units::imperial();
         assert_eq!(units.speed(), "mph");
     }
+
+    #[test]
+    fn test_units_speed_independent_of_preset() {
+        let units = Units {
+            speed: Speed::Knots,
+            ..Units::metric()
+        };
+        assert_eq!(units.speed(), "kn");
+        assert_eq!(units.temperature(), "celsius");
+        let units = Units {
+            speed: Speed::Beaufort,
+            ..Units::imperial()
+        };
+        assert_eq!(units.speed(), "kmh");
+        assert_eq!(units.precipitation(), "inch");
+    }
+
+    #[test]
+    fn test_units_to_celsius() {
+        assert_eq!(Units::metric().to_celsius(20.0), 20.0);
+        assert_eq!(Units::imperial().to_celsius(212.0), 100.0);
+        assert_eq!(Temperature::Kelvin.to_celsius(273.15), 0.0);
+    }
+
+    #[test]
+    fn test_units_from_celsius() {
+        assert_eq!(Units::metric().from_celsius(20.0), 20.0);
+        assert_eq!(Units::imperial().from_celsius(100.0), 212.0);
+    }
+
+    #[test]
+    fn test_units_to_mm() {
+        assert_eq!(Units::metric().to_mm(2.0), 2.0);
+        assert_eq!(Units::imperial().to_mm(2.0), 50.8);
+    }
+
+    #[test]
+    fn test_units_to_kmh() {
+        assert_eq!(Units::metric().to_kmh(10.0), 10.0);
+        assert_eq!(Units::imperial().to_kmh(10.0), 16.09344);
+        assert_eq!(Speed::Ms.to_kmh(10.0), 36.0);
+        assert_eq!(Speed::Knots.to_kmh(10.0), 18.52);
+    }
+
+    #[test]
+    fn test_speed_beaufort() {
+        // fresh breeze (force 5) starts at 29km/h
+        assert_eq!(Speed::Beaufort.from_kmh(29.0).round(), 5.0);
+        assert_eq!(Speed::Beaufort.from_kmh(0.0), 0.0);
+        assert_eq!(Speed::Beaufort.from_kmh(-29.0).round(), -5.0);
+        for kmh in [0.5, 5.99, 42.0, 150.0] {
+            assert_eq!(
+                round(Speed::Beaufort.to_kmh(Speed::Beaufort.from_kmh(kmh)), 3),
+                kmh
+            );
+        }
+    }
+
+    #[test]
+    fn test_speed_beaufort_matches_the_force() {
+        for kmh in [0.0, 0.99, 1.0, 5.99, 6.0, 28.99, 29.0, 117.9, 118.0, 200.0] {
+            let (force, _) = wind::beaufort(kmh);
+            assert_eq!(Speed::Beaufort.from_kmh(kmh).round(), f64::from(force));
+        }
+    }
+
+    #[test]
+    fn test_units_to_km() {
+        assert_eq!(Units::metric().to_km(10.0), 10.0);
+        assert_eq!(Units::imperial().to_km(10.0), 16.09344);
+    }
+
+    #[test]
+    fn test_units_from_km() {
+        assert_eq!(Units::metric().from_km(42.195), 42.195);
+        assert_eq!(Units::imperial().from_km(16.09344), 10.0);
+    }
+
+    #[test]
+    fn test_units_precipitation_metric() {
+        let units = Units::metric();
+        assert_eq!(units.precipitation(), "mm");
+    }
+
+    #[test]
+    fn test_units_precipitation_imperial() {
+        let units = Units::imperial();
+        assert_eq!(units.precipitation(), "inch");
+    }
 }
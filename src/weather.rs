@@ -1,15 +1,27 @@
-use anyhow::{anyhow, Result};
-use chrono::NaiveDateTime;
-use reqwest::{Client, Url};
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{self, Deserialize, Serialize};
+use serde_json::{Map, Value};
 
+use crate::air::AirQuality;
+use crate::alerts::{self, Alert};
+use crate::course::{self, Checkpoint, Segment, Waypoint};
 use crate::date_format::{self, OPEN_METEO_DATE_FORMAT};
-use crate::emoji::{self, emoji_for_weather};
-use crate::units::Units;
-use crate::wind;
+use crate::daylight::Daylight;
+use crate::emoji;
+use crate::ensemble::{self, Range, Spread};
+use crate::error::{Error, Result};
+use crate::gear::{self, Recommendation, Rules, Sport};
+use crate::http::Client;
+use crate::i18n::Locale;
+use crate::open_meteo::{self, group_by_coordinates};
+use crate::output::{Field, Options};
+use crate::stress::Stress;
+use crate::units::{round, Units};
+use crate::wind::Wind;
+use crate::wmo;
+use crate::Event;
 
 const API_URL: &str = "https://api.open-meteo.com/v1/forecast";
-const DATE_OUTPUT_FORMAT: &str = "%b %-d, %H:%M";
 
 #[derive(Serialize, Debug)]
 pub struct Notification {
@@ -28,12 +40,37 @@ pub struct Weather<'a> {
     #[serde(with = "date_format")]
     pub date: NaiveDateTime,
     pub weather_code: i8,
+    pub description: &'a str,
     pub probability_of_precipitation: i8,
     pub temperature: f64,
     pub feels_like: f64,
     pub humidity: i8,
     pub wind_speed: f64,
     pub wind_direction: i32,
+    pub wind: Wind,
+    pub precipitation: Option<f64>,
+    pub wind_gusts: Option<f64>,
+    pub uv_index: Option<f64>,
+    pub cloud_cover: Option<i8>,
+    pub dew_point: Option<f64>,
+    pub visibility: Option<f64>,
+    pub surface_pressure: Option<f64>,
+    pub solar_radiation: Option<f64>,
+    pub stress: Stress,
+    pub recommendations: Vec<Recommendation>,
+    pub course: Vec<Segment>,
+    pub checkpoints: Vec<Checkpoint<'a>>,
+    pub daylight: Option<Daylight>,
+    pub air_quality: Option<AirQuality>,
+    pub alerts: Vec<Alert>,
+    pub ensemble: Option<Spread>,
+
+    #[serde(with = "date_format::option", skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<NaiveDateTime>,
+
+    /// Of the location in seconds, to compare with times from other sources
+    #[serde(skip)]
+    pub utc_offset: i32,
 }
 
 impl Weather<'_> {
@@ -44,83 +81,312 @@ impl Weather<'_> {
         units: &Units,
         name: Option<String>,
         location: String,
+        client: &Client,
     ) -> Result<Self> {
-        let date = when.format("%Y-%m-%d");
-        let params = [
-            "temperature_2m",
-            "apparent_temperature",
-            "precipitation_probability",
-            "relativehumidity_2m",
-            "windspeed_10m",
-            "winddirection_10m",
-            "weathercode",
-        ]
-        .join(",");
-        let url = Url::parse_with_params(
-            API_URL,
-            &[
-                ("latitude", latitude.to_string()),
-                ("longitude", longitude.to_string()),
-                ("start_date", date.to_string()),
-                ("end_date", date.to_string()),
-                ("temperature_unit", units.temperature()),
-                ("windspeed_10m", units.speed()),
-                ("timezone", "auto".to_string()),
-                ("hourly", params),
-            ],
-        )?;
-
-        let resp = Client::new().get(url.to_string()).send().await?;
-        if !resp.status().is_success() {
-            return Err(anyhow!(
-                "HTTP request to {} returned {}: {}",
-                url,
-                resp.status(),
-                resp.text().await?
-            ));
+        let date = when.date();
+        let (hourly, as_of) = fetch(&[(latitude, longitude)], date, date, units, client).await?;
+        let mut weather = hourly[0].as_weather(when, name, location, units)?;
+        weather.as_of = as_of;
+        Ok(weather)
+    }
+
+    /// Fetches the forecast for many events with a single request: events at the same venue share
+    /// one location in the request, and the date range covers all events, so each event is sliced
+    /// from the hourly data of its location.
+    pub async fn for_events(events: &[Event], units: &Units, client: &Client) -> Result<Vec<Self>> {
+        let (Some(start), Some(end)) = (
+            events.iter().map(|event| event.when.date()).min(),
+            events.iter().map(|event| event.when.date()).max(),
+        ) else {
+            return Ok(vec![]);
+        };
+        let (coordinates, indexes) = group_by_coordinates(events);
+        let (hourly, as_of) = fetch(&coordinates, start, end, units, client).await?;
+        let mut forecasts = vec![];
+        for (event, idx) in events.iter().zip(indexes) {
+            let mut weather = hourly[idx].as_weather(
+                event.when,
+                event.name.clone(),
+                event.location.clone(),
+                units,
+            )?;
+            weather.as_of = as_of;
+            weather.along(&event.bearings);
+            weather.along_course(event, client).await?;
+            weather.window(event.when, event.end());
+            forecasts.push(weather);
         }
+        Ok(forecasts)
+    }
 
-        let data: Response = resp.json().await.map_err(|e| {
-            let message = format!("Failed to parse response JSON body from {url}: {e}");
-            anyhow!(message)
-        })?;
-        data.hourly.as_weather(when, name, location, units)
+    /// Fetches the forecast for the event from each weather model (e.g. `ecmwf_ifs025`) with a
+    /// single request.
+    pub async fn for_models(
+        event: &Event,
+        models: &[String],
+        units: &Units,
+        client: &Client,
+    ) -> Result<Vec<Self>> {
+        let date = event.when.date();
+        let mut query = query(&[(event.latitude, event.longitude)], date, date, units);
+        query.push(("models", models.join(",")));
+        let (responses, as_of) =
+            open_meteo::fetch::<Response<Map<String, Value>>>(API_URL, &query, 1, client).await?;
+        models
+            .iter()
+            .map(|model| {
+                let mut weather = responses[0].for_model(model)?.as_weather(
+                    event.when,
+                    event.name.clone(),
+                    event.location.clone(),
+                    units,
+                )?;
+                weather.as_of = as_of;
+                Ok(weather)
+            })
+            .collect()
     }
 
-    pub fn as_notification(&self) -> Result<Notification> {
-        let title = match &self.name {
-            Some(name) => format!(
-                "{} {} ({})",
-                emoji::CALENDAR,
-                name,
-                self.date.format(DATE_OUTPUT_FORMAT),
+    /// Fetches the hourly forecast at the event's location between `from` and `to` (inclusive).
+    pub async fn series(
+        event: &Event,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        units: &Units,
+        client: &Client,
+    ) -> Result<Vec<Self>> {
+        let coordinates = [(event.latitude, event.longitude)];
+        let (hourly, as_of) = fetch(&coordinates, from.date(), to.date(), units, client).await?;
+        let mut series =
+            hourly[0].series(from, to, event.name.clone(), event.location.clone(), units)?;
+        for weather in series.iter_mut() {
+            weather.as_of = as_of;
+        }
+        Ok(series)
+    }
+
+    pub fn recommend(&mut self, rules: &Rules, sports: &[Sport]) {
+        self.recommendations = rules.recommend(self, sports);
+    }
+
+    /// Fetches the spread of the ensemble forecast for the event hour.
+    pub async fn ensemble(&mut self, event: &Event, client: &Client) -> Result<()> {
+        self.ensemble = ensemble::for_event(event, &self.units, client).await?;
+        Ok(())
+    }
+
+    /// Attaches the alerts from the feed at `url` (if any) that overlap the event.
+    pub async fn alert(&mut self, event: &Event, url: Option<&str>, client: &Client) -> Result<()> {
+        if let Some(url) = url {
+            self.alerts = alerts::for_event(url, event, self.utc_offset, client).await?;
+        }
+        Ok(())
+    }
+
+    /// Breaks the wind down into headwind and crosswind for each leg of the course.
+    pub fn along(&mut self, bearings: &[f64]) {
+        self.course = course::segments(bearings, self.wind_speed, self.wind_direction, &self.units);
+    }
+
+    /// Fetches the forecast at each waypoint of the event's course for the estimated time of
+    /// arrival, with a single request for all waypoints.
+    pub async fn along_course(&mut self, event: &Event, client: &Client) -> Result<()> {
+        let (Some(speed), Some(last)) = (event.speed, event.course.last()) else {
+            return Ok(());
+        };
+        let coordinates = event
+            .course
+            .iter()
+            .map(|waypoint| (waypoint.latitude, waypoint.longitude))
+            .collect::<Vec<(f64, f64)>>();
+        let Some(end) = course::arrival(event.when, last, speed) else {
+            return Err(Error::Validation(format!(
+                "The goal speed should be above zero, got {speed}"
+            )));
+        };
+        let end = end.date();
+        let (hourly, _) = fetch(&coordinates, event.when.date(), end, &self.units, client).await?;
+        self.checkpoints = checkpoints(&event.course, &hourly, event.when, speed, &self.units)?;
+        Ok(())
+    }
+
+    /// Flags the event as in the dark if any part of it is before sunrise or after sunset.
+    pub fn window(&mut self, start: NaiveDateTime, end: NaiveDateTime) {
+        if let Some(daylight) = self.daylight.as_mut() {
+            daylight.window(start, end);
+        }
+    }
+
+    fn field(&self, field: &Field, locale: &Locale) -> Option<String> {
+        let temperature = self.units.temperature.symbol();
+        let speed = self.units.speed.symbol();
+        let precipitation = self.units.precipitation.symbol();
+        let distance = self.units.distance.symbol();
+        Some(match field {
+            Field::Precipitation => format!(
+                "{}{} {}",
+                self.precipitation?,
+                precipitation,
+                locale.t("of rain")
             ),
+            Field::Gusts => format!(
+                "{}{} {}",
+                self.wind_gusts?.round(),
+                speed,
+                locale.t("gusts")
+            ),
+            Field::Uv => format!("{} {}", locale.t("UV index"), self.uv_index?.round()),
+            Field::CloudCover => format!("{}% {}", self.cloud_cover?, locale.t("cloud cover")),
+            Field::DewPoint => format!(
+                "{}{} {}",
+                self.dew_point?.round(),
+                temperature,
+                locale.t("dew point")
+            ),
+            Field::Visibility => {
+                let value = self.units.from_km(self.visibility? / 1_000.0);
+                format!(
+                    "{}{} {}",
+                    (value * 10.0).round() / 10.0,
+                    distance,
+                    locale.t("visibility")
+                )
+            }
+            Field::Pressure => format!("{}hPa", self.surface_pressure?.round()),
+        })
+    }
+
+    pub fn as_notification(&self, options: &Options) -> Result<Notification> {
+        let locale = &options.locale;
+        let title = match &self.name {
+            Some(name) => format!("{} {} ({})", emoji::CALENDAR, name, locale.date(&self.date),),
             None => "".to_string(),
         };
         let subtitle = format!("{} {}", emoji::GLOBE, self.location);
-        let temperature = match self.units {
-            Units::Metric => "C",
-            Units::Imperial => "F",
+        let temperature = self.units.temperature.symbol();
+        let speed = self.units.speed.symbol();
+        // with the ensemble, its median leads the range instead of the deterministic value
+        let spread = |value: f64, unit: &str, range: fn(&Spread) -> Option<&Range>| match self
+            .ensemble
+            .as_ref()
+            .and_then(range)
+        {
+            Some(range) => format!("{}{} {}", range.median.round(), unit, range.as_string(0)),
+            None => format!("{}{}", value.round(), unit),
         };
-        let speed = match self.units {
-            Units::Metric => "km/h",
-            Units::Imperial => "mph",
+        let precipitation = match self
+            .ensemble
+            .as_ref()
+            .and_then(|s| s.precipitation.as_ref())
+        {
+            Some(range) => format!(
+                " & {}{} {}",
+                round(range.median, 1),
+                self.units.precipitation.symbol(),
+                range.as_string(1)
+            ),
+            None => "".to_string(),
         };
-        let body = format!(
-            "{} {}°{} (feels like {}°{})\n{} {}% chance of rain & {}% humidity\n{} {}{} {}",
-            emoji::emoji_for_weather(self.weather_code)?,
-            self.temperature.round(),
-            temperature,
+        let mut body = format!(
+            "{} {}, {} ({} {}{})\n{} {}% {}{} & {}% {}\n{} {} {}",
+            self.icon,
+            locale.t(self.description),
+            spread(self.temperature, temperature, |s| s.temperature.as_ref()),
+            locale.t("feels like"),
             self.feels_like.round(),
             temperature,
             emoji::PRECIPITATION,
             self.probability_of_precipitation,
+            locale.t("chance of rain"),
+            precipitation,
             self.humidity,
+            locale.t("humidity"),
             emoji::WIND,
-            self.wind_speed.round(),
-            speed,
-            wind::wind_direction(self.wind_direction)?,
+            spread(self.wind_speed, speed, |s| s.wind_speed.as_ref()),
+            options.wind.as_string(&self.wind, locale),
         );
+        if !self.alerts.is_empty() {
+            let alerts = self
+                .alerts
+                .iter()
+                .map(|alert| alert.as_string(locale))
+                .collect::<Vec<String>>();
+            body = format!("{} {}\n{}", emoji::ALERT, alerts.join("; "), body);
+        }
+        body.push_str(&format!(
+            "\n{} WBGT {}{}",
+            self.stress.risk().emoji(),
+            self.stress.wbgt.round(),
+            temperature
+        ));
+        if let Some(heat_index) = self.stress.heat_index {
+            body.push_str(&format!(
+                " & {} {}{}",
+                locale.t("heat index"),
+                heat_index.round(),
+                temperature
+            ));
+        }
+        if let Some(wind_chill) = self.stress.wind_chill {
+            body.push_str(&format!(
+                " & {} {}{}",
+                locale.t("wind chill"),
+                wind_chill.round(),
+                temperature
+            ));
+        }
+        if let Some(daylight) = &self.daylight {
+            body.push_str(&format!(
+                "\n{} {}",
+                emoji::SUNRISE,
+                daylight.as_string(locale)
+            ));
+        }
+        if let Some(air_quality) = self
+            .air_quality
+            .as_ref()
+            .and_then(|air_quality| air_quality.as_string(&options.air_quality, locale))
+        {
+            body.push_str(&format!("\n{} {}", emoji::AIR, air_quality));
+        }
+        let fields = options
+            .fields
+            .iter()
+            .filter_map(|field| self.field(field, locale))
+            .collect::<Vec<String>>();
+        if !fields.is_empty() {
+            body.push_str(&format!("\n{} {}", emoji::CHART, fields.join(" & ")));
+        }
+        if !self.course.is_empty() {
+            let segments = self
+                .course
+                .iter()
+                .map(|segment| segment.as_string(&self.units, &options.locale))
+                .collect::<Vec<String>>();
+            body.push_str(&format!("\n{} {}", emoji::COMPASS, segments.join("; ")));
+        }
+        for checkpoint in &self.checkpoints {
+            body.push_str(&format!(
+                "\n{} {}",
+                emoji::PIN,
+                checkpoint.as_string(&self.units, &options.locale)
+            ));
+        }
+        if !self.recommendations.is_empty() {
+            body.push_str(&format!(
+                "\n{} {}",
+                emoji::GEAR,
+                gear::as_line(&self.recommendations, locale)
+            ));
+        }
+        if let Some(as_of) = self.as_of {
+            body.push_str(&format!(
+                "\n{} {} {}",
+                emoji::CLOCK,
+                locale.t("as of"),
+                locale.date(&as_of)
+            ));
+        }
 
         Ok(Notification {
             title,
@@ -128,12 +394,9 @@ impl Weather<'_> {
             body,
         })
     }
-    pub fn as_string(&self, json: bool) -> Result<String> {
-        if json {
-            return Ok(serde_json::to_string(&self)?);
-        }
 
-        let notification = self.as_notification()?;
+    pub fn as_string(&self, options: &Options) -> Result<String> {
+        let notification = self.as_notification(options)?;
         Ok(format!(
             "{} {}\n{}",
             notification.title,
@@ -143,9 +406,107 @@ impl Weather<'_> {
     }
 }
 
+fn query(
+    coordinates: &[(f64, f64)],
+    start: NaiveDate,
+    end: NaiveDate,
+    units: &Units,
+) -> Vec<(&'static str, String)> {
+    let params = [
+        "temperature_2m",
+        "apparent_temperature",
+        "precipitation_probability",
+        "relativehumidity_2m",
+        "windspeed_10m",
+        "winddirection_10m",
+        "weathercode",
+        "is_day",
+        "precipitation",
+        "wind_gusts_10m",
+        "uv_index",
+        "cloud_cover",
+        "dew_point_2m",
+        "visibility",
+        "surface_pressure",
+        "shortwave_radiation",
+    ]
+    .join(",");
+    let mut query = open_meteo::locations(coordinates).to_vec();
+    query.extend([
+        ("start_date", start.to_string()),
+        ("end_date", end.to_string()),
+        ("temperature_unit", units.temperature()),
+        ("wind_speed_unit", units.speed()),
+        ("precipitation_unit", units.precipitation()),
+        ("timezone", "auto".to_string()),
+        ("hourly", params),
+    ]);
+    query
+}
+
+async fn fetch(
+    coordinates: &[(f64, f64)],
+    start: NaiveDate,
+    end: NaiveDate,
+    units: &Units,
+    client: &Client,
+) -> Result<(Vec<Hourly>, Option<NaiveDateTime>)> {
+    let query = query(coordinates, start, end, units);
+    let (responses, as_of) =
+        open_meteo::fetch::<Response>(API_URL, &query, coordinates.len(), client).await?;
+    Ok((
+        responses.into_iter().map(Response::into_hourly).collect(),
+        as_of,
+    ))
+}
+
 #[derive(Deserialize, Debug)]
-struct Response {
-    hourly: Hourly,
+struct Response<H = Hourly> {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    #[serde(default)]
+    utc_offset_seconds: i32,
+    hourly: H,
+}
+
+impl<H> Response<H> {
+    fn place(&self) -> Option<(f64, f64, i32)> {
+        Some((self.latitude?, self.longitude?, self.utc_offset_seconds))
+    }
+}
+
+impl Response {
+    fn into_hourly(self) -> Hourly {
+        let place = self.place();
+        let mut hourly = self.hourly;
+        hourly.place = place;
+        hourly
+    }
+}
+
+impl Response<Map<String, Value>> {
+    /// The hourly forecast from one of the models, whose variables are suffixed with its name when
+    /// the request has more than one model (e.g. `temperature_2m_gfs_seamless`).
+    fn for_model(&self, model: &str) -> Result<Hourly> {
+        let suffix = format!("_{model}");
+        let mut variables = Map::new();
+        for (name, values) in &self.hourly {
+            match name.strip_suffix(&suffix) {
+                Some(variable) => {
+                    variables.insert(variable.to_string(), values.clone());
+                }
+                None => {
+                    variables
+                        .entry(name.clone())
+                        .or_insert_with(|| values.clone());
+                }
+            }
+        }
+        let mut hourly: Hourly = serde_json::from_value(Value::Object(variables))
+            .map_err(|e| Error::Parse(format!("Failed to parse the forecast from {model}: {e}")))?;
+        hourly.place = self.place();
+        Ok(hourly)
+    }
 }
 
 #[derive(Debug)]
@@ -158,6 +519,15 @@ struct HourlyItem {
     windspeed_10m: f64,
     winddirection_10m: i32,
     weathercode: i8,
+    is_day: bool,
+    precipitation: Option<f64>,
+    wind_gusts_10m: Option<f64>,
+    uv_index: Option<f64>,
+    cloud_cover: Option<i8>,
+    dew_point_2m: Option<f64>,
+    visibility: Option<f64>,
+    surface_pressure: Option<f64>,
+    shortwave_radiation: Option<f64>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -170,6 +540,30 @@ struct Hourly {
     windspeed_10m: Vec<Option<f64>>,
     winddirection_10m: Vec<Option<i32>>,
     weathercode: Vec<Option<i8>>,
+    #[serde(default)]
+    is_day: Vec<Option<u8>>,
+
+    // variables added later are optional, so cached responses without them are still valid
+    #[serde(default)]
+    precipitation: Vec<Option<f64>>,
+    #[serde(default)]
+    wind_gusts_10m: Vec<Option<f64>>,
+    #[serde(default)]
+    uv_index: Vec<Option<f64>>,
+    #[serde(default)]
+    cloud_cover: Vec<Option<i8>>,
+    #[serde(default)]
+    dew_point_2m: Vec<Option<f64>>,
+    #[serde(default)]
+    visibility: Vec<Option<f64>>,
+    #[serde(default)]
+    surface_pressure: Vec<Option<f64>>,
+    #[serde(default)]
+    shortwave_radiation: Vec<Option<f64>>,
+
+    /// Latitude, longitude and UTC offset in seconds of the location, from the response
+    #[serde(skip)]
+    place: Option<(f64, f64, i32)>,
 }
 
 impl Hourly {
@@ -183,6 +577,15 @@ impl Hourly {
             windspeed_10m: self.windspeed_10m[idx]?,
             winddirection_10m: self.winddirection_10m[idx]?,
             weathercode: self.weathercode[idx]?,
+            is_day: self.is_day.get(idx).copied().flatten() != Some(0),
+            precipitation: self.precipitation.get(idx).copied().flatten(),
+            wind_gusts_10m: self.wind_gusts_10m.get(idx).copied().flatten(),
+            uv_index: self.uv_index.get(idx).copied().flatten(),
+            cloud_cover: self.cloud_cover.get(idx).copied().flatten(),
+            dew_point_2m: self.dew_point_2m.get(idx).copied().flatten(),
+            visibility: self.visibility.get(idx).copied().flatten(),
+            surface_pressure: self.surface_pressure.get(idx).copied().flatten(),
+            shortwave_radiation: self.shortwave_radiation.get(idx).copied().flatten(),
         })
     }
 
@@ -200,48 +603,289 @@ impl Hourly {
                 (item, diff)
             })
             .min_by_key(|(_, diff)| *diff)
-            .ok_or(anyhow!("No weather data found"))?
+            .ok_or(Error::Parse("No weather data found".to_string()))?
             .0;
+        let mut weather = item.into_weather(name, location, units)?;
+        weather.daylight = self.daylight(weather.date.date());
+        weather.utc_offset = self.place.map_or(0, |(_, _, utc_offset)| utc_offset);
+        Ok(weather)
+    }
+
+    fn daylight(&self, date: NaiveDate) -> Option<Daylight> {
+        let (latitude, longitude, utc_offset) = self.place?;
+        Some(Daylight::new(date, latitude, longitude, utc_offset))
+    }
+
+    /// Returns the weather for every hour between `from` and `to` (inclusive).
+    fn series<'a>(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        name: Option<String>,
+        location: String,
+        units: &Units,
+    ) -> Result<Vec<Weather<'a>>> {
+        (0..self.time.len())
+            .filter_map(|idx| self.item(idx))
+            .filter(|item| item.time >= from && item.time <= to)
+            .map(|item| item.into_weather(name.clone(), location.clone(), units))
+            .collect()
+    }
+}
 
+impl HourlyItem {
+    fn into_weather<'a>(
+        self,
+        name: Option<String>,
+        location: String,
+        units: &Units,
+    ) -> Result<Weather<'a>> {
+        // Kelvin and Beaufort are not available in the API, they are converted from the response
+        let temperature = units.from_response_temperature(self.temperature_2m);
+        let wind_speed = units.from_response_speed(self.windspeed_10m);
         Ok(Weather {
             name,
             location,
-            weather_code: item.weathercode,
-            icon: emoji_for_weather(item.weathercode)?,
+            weather_code: self.weathercode,
+            description: wmo::description(self.weathercode),
+            icon: wmo::emoji(self.weathercode, self.is_day),
             units: units.clone(),
-            date: item.time,
-            probability_of_precipitation: item.precipitation_probability,
-            temperature: item.temperature_2m,
-            feels_like: item.apparent_temperature,
-            humidity: item.relativehumidity_2m,
-            wind_speed: item.windspeed_10m,
-            wind_direction: item.winddirection_10m,
+            date: self.time,
+            probability_of_precipitation: self.precipitation_probability,
+            temperature,
+            feels_like: units.from_response_temperature(self.apparent_temperature),
+            humidity: self.relativehumidity_2m,
+            wind_speed,
+            wind_direction: self.winddirection_10m,
+            wind: Wind::new(self.winddirection_10m, units.to_kmh(wind_speed)),
+            precipitation: self.precipitation,
+            wind_gusts: self.wind_gusts_10m.map(|v| units.from_response_speed(v)),
+            uv_index: self.uv_index,
+            cloud_cover: self.cloud_cover,
+            dew_point: self
+                .dew_point_2m
+                .map(|v| units.from_response_temperature(v)),
+            visibility: self.visibility,
+            surface_pressure: self.surface_pressure,
+            solar_radiation: self.shortwave_radiation,
+            stress: Stress::new(
+                temperature,
+                self.relativehumidity_2m.into(),
+                wind_speed,
+                self.shortwave_radiation,
+                units,
+            ),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
         })
     }
 }
 
+/// Weather at each waypoint (with the hourly forecast at the same index) at its estimated time of
+/// arrival, leaving `start` at `speed` km/h.
+fn checkpoints<'a>(
+    course: &[Waypoint],
+    hourly: &[Hourly],
+    start: NaiveDateTime,
+    speed: f64,
+    units: &Units,
+) -> Result<Vec<Checkpoint<'a>>> {
+    course
+        .iter()
+        .zip(hourly)
+        .map(|(waypoint, hourly)| {
+            let arrival = course::arrival(start, waypoint, speed).ok_or(Error::Validation(
+                format!("The goal speed should be above zero, got {speed}"),
+            ))?;
+            let weather = hourly.as_weather(arrival, None, String::new(), units)?;
+            Ok(Checkpoint::new(waypoint, arrival, &weather))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::i18n::Locale;
+
+    const HOURLY: &str = r#"{
+        "time": ["2021-05-20T08:00", "2021-05-20T09:00", "2021-05-21T08:00"],
+        "temperature_2m": [17.0, 18.0, 21.0],
+        "apparent_temperature": [16.0, 17.0, 20.0],
+        "relativehumidity_2m": [80, 75, 60],
+        "precipitation_probability": [10, null, 40],
+        "windspeed_10m": [9.0, 10.0, 12.0],
+        "winddirection_10m": [180, 190, 270],
+        "weathercode": [1, 2, 61]
+    }"#;
+
+    #[test]
+    fn test_response_with_daylight() {
+        let body = format!(
+            r#"{{"latitude": 45.42, "longitude": -75.7, "utc_offset_seconds": -14400, "hourly": {HOURLY}}}"#
+        );
+        let response: Response = serde_json::from_str(&body).unwrap();
+        let hourly = [response.into_hourly()];
+        let when =
+            NaiveDateTime::parse_from_str("2021-05-20 8:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let weather = hourly[0]
+            .as_weather(when, None, "Location".to_string(), &Units::metric())
+            .unwrap();
+        let daylight = weather.daylight.unwrap();
+        assert_eq!(
+            daylight.sunrise.unwrap().format("%H:%M").to_string(),
+            "05:28"
+        );
+        assert_eq!(weather.utc_offset, -14_400);
+    }
+
+    #[test]
+    fn test_hourly_as_weather_slices_each_event() {
+        let hourly: Hourly = serde_json::from_str(HOURLY).unwrap();
+        let saturday = NaiveDateTime::parse_from_str("2021-05-20 9:10", "%Y-%m-%d %H:%M").unwrap();
+        let sunday = NaiveDateTime::parse_from_str("2021-05-21 8:00", "%Y-%m-%d %H:%M").unwrap();
+
+        let weather = hourly
+            .as_weather(saturday, None, "Location".to_string(), &Units::metric())
+            .unwrap();
+        assert_eq!(weather.temperature, 18.0);
+        assert_eq!(weather.probability_of_precipitation, 0);
+
+        let weather = hourly
+            .as_weather(sunday, None, "Location".to_string(), &Units::metric())
+            .unwrap();
+        assert_eq!(weather.temperature, 21.0);
+        assert_eq!(weather.weather_code, 61);
+    }
+
+    #[test]
+    fn test_hourly_as_weather_at_night() {
+        let hourly = HOURLY.replace(r#""weathercode""#, r#""is_day": [0, 1, 1], "weathercode""#);
+        let hourly: Hourly = serde_json::from_str(&hourly).unwrap();
+        let when = NaiveDateTime::parse_from_str("2021-05-20 8:00", "%Y-%m-%d %H:%M").unwrap();
+        let weather = hourly
+            .as_weather(when, None, "Location".to_string(), &Units::metric())
+            .unwrap();
+        assert_eq!(weather.icon, "🌙");
+        assert_eq!(weather.description, "mainly clear");
+    }
+
+    #[test]
+    fn test_checkpoints_arrival() {
+        let hourly: Hourly = serde_json::from_str(HOURLY).unwrap();
+        let start = NaiveDateTime::parse_from_str("2021-05-20 8:00", "%Y-%m-%d %H:%M").unwrap();
+        let waypoint = Waypoint {
+            latitude: 45.0,
+            longitude: -75.0,
+            distance: 9.0,
+            bearing: 0.0,
+        };
+        let checkpoints =
+            checkpoints(&[waypoint], &[hourly], start, 12.0, &Units::metric()).unwrap();
+        assert_eq!(
+            checkpoints[0].arrival,
+            NaiveDateTime::parse_from_str("2021-05-20 8:45", "%Y-%m-%d %H:%M").unwrap()
+        );
+        assert_eq!(checkpoints[0].temperature, 18.0);
+    }
+
+    #[test]
+    fn test_response_for_model() {
+        let body = r#"{
+            "latitude": 45.42,
+            "longitude": -75.7,
+            "hourly": {
+                "time": ["2021-05-20T08:00"],
+                "temperature_2m_ecmwf_ifs025": [17.0],
+                "temperature_2m_gfs_seamless": [21.0],
+                "apparent_temperature_ecmwf_ifs025": [16.0],
+                "apparent_temperature_gfs_seamless": [21.0],
+                "relativehumidity_2m_ecmwf_ifs025": [80],
+                "relativehumidity_2m_gfs_seamless": [70],
+                "precipitation_probability_ecmwf_ifs025": [10],
+                "precipitation_probability_gfs_seamless": [50],
+                "windspeed_10m_ecmwf_ifs025": [9.0],
+                "windspeed_10m_gfs_seamless": [14.0],
+                "winddirection_10m_ecmwf_ifs025": [270],
+                "winddirection_10m_gfs_seamless": [225],
+                "weathercode_ecmwf_ifs025": [1],
+                "weathercode_gfs_seamless": [2]
+            }
+        }"#;
+        let response: Response<Map<String, Value>> = serde_json::from_str(body).unwrap();
+        let when = NaiveDateTime::parse_from_str("2021-05-20 8:00", "%Y-%m-%d %H:%M").unwrap();
+        let weather = |model| {
+            response
+                .for_model(model)
+                .unwrap()
+                .as_weather(when, None, "Location".to_string(), &Units::metric())
+                .unwrap()
+        };
+        assert_eq!(weather("ecmwf_ifs025").temperature, 17.0);
+        assert_eq!(weather("gfs_seamless").temperature, 21.0);
+        assert_eq!(weather("gfs_seamless").probability_of_precipitation, 50);
+        assert!(response.for_model("icon_seamless").is_err());
+    }
+
+    #[test]
+    fn test_hourly_series() {
+        let hourly: Hourly = serde_json::from_str(HOURLY).unwrap();
+        let from = NaiveDateTime::parse_from_str("2021-05-20 8:30", "%Y-%m-%d %H:%M").unwrap();
+        let to = NaiveDateTime::parse_from_str("2021-05-21 8:00", "%Y-%m-%d %H:%M").unwrap();
+
+        let series = hourly
+            .series(from, to, None, "Location".to_string(), &Units::metric())
+            .unwrap();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].temperature, 18.0);
+        assert_eq!(series[1].temperature, 21.0);
+    }
 
     #[test]
     fn test_weather_as_notification() {
         let weather = Weather {
             name: Some("Event".to_string()),
             location: "Location".to_string(),
-            units: Units::Metric,
+            units: Units::metric(),
             icon: "☀️",
             date: NaiveDateTime::parse_from_str("2021-05-20 8:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
             weather_code: 1,
+            description: "mainly clear",
             probability_of_precipitation: 20,
             temperature: 25.0,
             feels_like: 28.0,
             humidity: 80,
             wind_speed: 10.0,
             wind_direction: 180,
+            wind: Wind::new(180, 10.0),
+            precipitation: Some(0.2),
+            wind_gusts: Some(25.0),
+            uv_index: Some(6.0),
+            cloud_cover: Some(40),
+            dew_point: Some(12.0),
+            visibility: Some(24_140.0),
+            surface_pressure: Some(1013.2),
+            solar_radiation: Some(450.0),
+            stress: Stress::new(25.0, 80.0, 10.0, Some(450.0), &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
         };
 
-        let result = weather.as_notification();
+        let result = weather.as_notification(&Options::default());
         assert!(result.is_ok());
 
         let notification = result.unwrap();
@@ -249,10 +893,232 @@ mod tests {
         assert_eq!(notification.subtitle, "🌐 Location");
 
         let lines = notification.body.split('\n').collect::<Vec<&str>>();
-        assert_eq!(lines.len(), 3);
-        assert_eq!(lines[0], "☀️ 25°C (feels like 28°C)");
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "☀️ mainly clear, 25°C (feels like 28°C)");
         assert_eq!(lines[1], "☔ 20% chance of rain & 80% humidity");
         assert_eq!(lines[2], "💨 10km/h S");
+        assert_eq!(lines[3], "🔴 WBGT 25°C");
+    }
+
+    #[test]
+    fn test_weather_as_notification_with_fields_and_gear() {
+        let mut weather = Weather {
+            name: Some("Event".to_string()),
+            location: "Location".to_string(),
+            units: Units::metric(),
+            icon: "☀️",
+            date: NaiveDateTime::parse_from_str("2021-05-20 8:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
+            weather_code: 1,
+            description: "mainly clear",
+            probability_of_precipitation: 20,
+            temperature: 25.0,
+            feels_like: 28.0,
+            humidity: 80,
+            wind_speed: 10.0,
+            wind_direction: 180,
+            wind: Wind::new(180, 10.0),
+            precipitation: Some(0.2),
+            wind_gusts: Some(25.0),
+            uv_index: Some(6.0),
+            cloud_cover: Some(40),
+            dew_point: Some(12.0),
+            visibility: None,
+            surface_pressure: Some(1013.2),
+            solar_radiation: Some(450.0),
+            stress: Stress::new(25.0, 80.0, 10.0, Some(450.0), &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
+        };
+        let options = Options {
+            fields: vec![
+                Field::Precipitation,
+                Field::Gusts,
+                Field::Visibility,
+                Field::DewPoint,
+            ],
+            ..Default::default()
+        };
+        weather.recommend(&Rules::default(), &[Sport::Run]);
+
+        let notification = weather.as_notification(&options).unwrap();
+        let lines = notification.body.split('\n').collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[4], "📊 0.2mm of rain & 25km/h gusts & 12°C dew point");
+        assert_eq!(lines[5], "🎒 run: sunscreen, cap");
+    }
+
+    #[test]
+    fn test_weather_as_notification_with_wind_chill_and_course() {
+        let mut weather = Weather {
+            name: Some("Event".to_string()),
+            location: "Location".to_string(),
+            units: Units::metric(),
+            icon: "🌨️",
+            date: NaiveDateTime::parse_from_str("2021-01-20 8:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
+            weather_code: 73,
+            description: "moderate snow fall",
+            probability_of_precipitation: 60,
+            temperature: -15.0,
+            feels_like: -24.0,
+            humidity: 70,
+            wind_speed: 30.0,
+            wind_direction: 270,
+            wind: Wind::new(270, 30.0),
+            precipitation: None,
+            wind_gusts: None,
+            uv_index: None,
+            cloud_cover: None,
+            dew_point: None,
+            visibility: None,
+            surface_pressure: None,
+            solar_radiation: None,
+            stress: Stress::new(-15.0, 70.0, 30.0, None, &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
+        };
+
+        weather.along(&[90.0, 270.0]);
+
+        let notification = weather.as_notification(&Options::default()).unwrap();
+        let lines = notification.body.split('\n').collect::<Vec<&str>>();
+        assert_eq!(lines[3], "🟡 WBGT -16°C & wind chill -26°C");
+        assert_eq!(
+            lines[4],
+            "🧭 90°: 30km/h tailwind & 0km/h crosswind from the right; 270°: 30km/h headwind & 0km/h crosswind from the right"
+        );
+    }
+
+    #[test]
+    fn test_weather_as_notification_with_alerts() {
+        let hourly: Hourly = serde_json::from_str(HOURLY).unwrap();
+        let when = NaiveDateTime::parse_from_str("2021-05-20 8:00", "%Y-%m-%d %H:%M").unwrap();
+        let mut weather = hourly
+            .as_weather(when, None, "Location".to_string(), &Units::metric())
+            .unwrap();
+        weather.alerts = vec![Alert {
+            event: "thunderstorm".to_string(),
+            headline: None,
+            severity: Some("Severe".to_string()),
+            area: None,
+            onset: None,
+            expires: Some(
+                NaiveDateTime::parse_from_str("2021-05-20 20:00", "%Y-%m-%d %H:%M").unwrap(),
+            ),
+        }];
+
+        let notification = weather.as_notification(&Options::default()).unwrap();
+        assert_eq!(
+            notification.body.split('\n').next().unwrap(),
+            "🚨 thunderstorm (severe, until May 20, 20:00)"
+        );
+    }
+
+    #[test]
+    fn test_weather_as_notification_with_ensemble() {
+        let hourly: Hourly = serde_json::from_str(HOURLY).unwrap();
+        let when = NaiveDateTime::parse_from_str("2021-05-20 8:00", "%Y-%m-%d %H:%M").unwrap();
+        let mut weather = hourly
+            .as_weather(when, None, "Location".to_string(), &Units::metric())
+            .unwrap();
+        let range = |median, p10, p90| Some(Range { median, p10, p90 });
+        weather.ensemble = Some(Spread {
+            temperature: range(18.4, 14.2, 20.6),
+            precipitation: range(0.4, 0.0, 2.25),
+            wind_speed: range(11.0, 6.0, 14.0),
+        });
+
+        let notification = weather.as_notification(&Options::default()).unwrap();
+        let lines = notification.body.split('\n').collect::<Vec<&str>>();
+        assert_eq!(lines[0], "☀️ mainly clear, 18°C (14–21) (feels like 16°C)");
+        assert_eq!(
+            lines[1],
+            "☔ 10% chance of rain & 0.4mm (0–2.3) & 80% humidity"
+        );
+        assert_eq!(lines[2], "💨 11km/h (6–14) S");
+    }
+
+    #[test]
+    fn test_weather_as_notification_in_french() {
+        let hourly: Hourly = serde_json::from_str(HOURLY).unwrap();
+        let when = NaiveDateTime::parse_from_str("2021-05-20 8:00", "%Y-%m-%d %H:%M").unwrap();
+        let weather = hourly
+            .as_weather(
+                when,
+                Some("10K".to_string()),
+                "Location".to_string(),
+                &Units::metric(),
+            )
+            .unwrap();
+        let options = Options {
+            locale: Locale::Fr,
+            ..Default::default()
+        };
+
+        let notification = weather.as_notification(&options).unwrap();
+        let lines = notification.body.split('\n').collect::<Vec<&str>>();
+        assert_eq!(notification.title, "🗓️ 10K (20 mai, 08:00)");
+        assert_eq!(lines[0], "☀️ plutôt dégagé, 17°C (ressenti 16°C)");
+        assert_eq!(lines[1], "☔ 10% de risque de pluie & 80% d'humidité");
+        assert_eq!(lines[2], "💨 9km/h S");
+    }
+
+    #[test]
+    fn test_weather_as_notification_from_cache() {
+        let weather = Weather {
+            name: Some("Event".to_string()),
+            location: "Location".to_string(),
+            units: Units::metric(),
+            icon: "☀️",
+            date: NaiveDateTime::parse_from_str("2021-05-20 8:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
+            weather_code: 1,
+            description: "mainly clear",
+            probability_of_precipitation: 20,
+            temperature: 25.0,
+            feels_like: 28.0,
+            humidity: 80,
+            wind_speed: 10.0,
+            wind_direction: 180,
+            wind: Wind::new(180, 10.0),
+            precipitation: Some(0.2),
+            wind_gusts: Some(25.0),
+            uv_index: Some(6.0),
+            cloud_cover: Some(40),
+            dew_point: Some(12.0),
+            visibility: Some(24_140.0),
+            surface_pressure: Some(1013.2),
+            solar_radiation: Some(450.0),
+            stress: Stress::new(25.0, 80.0, 10.0, Some(450.0), &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: Some(
+                NaiveDateTime::parse_from_str("2021-05-18 7:12:00", "%Y-%m-%d %H:%M:%S").unwrap(),
+            ),
+            utc_offset: 0,
+        };
+
+        let notification = weather.as_notification(&Options::default()).unwrap();
+        let lines = notification.body.split('\n').collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[4], "🕒 as of May 18, 07:12");
     }
 
     #[test]
@@ -260,25 +1126,45 @@ mod tests {
         let weather = Weather {
             name: Some("Event".to_string()),
             location: "Location".to_string(),
-            units: Units::Metric,
+            units: Units::metric(),
             icon: "☀️",
             date: NaiveDateTime::parse_from_str("2021-05-20 8:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
             weather_code: 1,
+            description: "mainly clear",
             probability_of_precipitation: 20,
             temperature: 25.0,
             feels_like: 28.0,
             humidity: 80,
             wind_speed: 10.0,
             wind_direction: 180,
+            wind: Wind::new(180, 10.0),
+            precipitation: Some(0.2),
+            wind_gusts: Some(25.0),
+            uv_index: Some(6.0),
+            cloud_cover: Some(40),
+            dew_point: Some(12.0),
+            visibility: Some(24_140.0),
+            surface_pressure: Some(1013.2),
+            solar_radiation: Some(450.0),
+            stress: Stress::new(25.0, 80.0, 10.0, Some(450.0), &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
         };
 
-        let result = weather.as_string(false);
+        let result = weather.as_string(&Options::default());
         assert!(result.is_ok());
         assert_eq!(
             result.unwrap(),
             [
                 "🗓️ Event (May 20, 08:00) 🌐 Location",
-                "☀️ 25°C (feels like 28°C) ☔ 20% chance of rain & 80% humidity 💨 10km/h S"
+                "☀️ mainly clear, 25°C (feels like 28°C) ☔ 20% chance of rain & 80% humidity 💨 10km/h S 🔴 WBGT 25°C"
             ]
             .join("\n")
         );
@@ -289,23 +1175,43 @@ mod tests {
         let weather = Weather {
             name: Some("Event".to_string()),
             location: "Location".to_string(),
-            units: Units::Metric,
+            units: Units::metric(),
             icon: "☀️",
             date: NaiveDateTime::parse_from_str("2021-05-20 8:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
             weather_code: 1,
+            description: "mainly clear",
             probability_of_precipitation: 20,
             temperature: 25.0,
             feels_like: 28.0,
             humidity: 80,
             wind_speed: 10.0,
             wind_direction: 180,
+            wind: Wind::new(180, 10.0),
+            precipitation: Some(0.2),
+            wind_gusts: Some(25.0),
+            uv_index: Some(6.0),
+            cloud_cover: Some(40),
+            dew_point: Some(12.0),
+            visibility: Some(24_140.0),
+            surface_pressure: Some(1013.2),
+            solar_radiation: Some(450.0),
+            stress: Stress::new(25.0, 80.0, 10.0, Some(450.0), &Units::metric()),
+            recommendations: vec![],
+            course: vec![],
+            checkpoints: vec![],
+            daylight: None,
+            air_quality: None,
+            alerts: vec![],
+            ensemble: None,
+            as_of: None,
+            utc_offset: 0,
         };
 
-        let result = weather.as_string(true);
+        let result = serde_json::to_string(&weather);
         assert!(result.is_ok());
         assert_eq!(
             result.unwrap(),
-            r#"{"name":"Event","location":"Location","units":"Metric","icon":"☀️","date":"2021-05-20 08:00:00","weather_code":1,"probability_of_precipitation":20,"temperature":25.0,"feels_like":28.0,"humidity":80,"wind_speed":10.0,"wind_direction":180}"#
+            r#"{"name":"Event","location":"Location","units":{"temperature":"celsius","speed":"kmh","precipitation":"mm","distance":"km"},"icon":"☀️","date":"2021-05-20 08:00:00","weather_code":1,"description":"mainly clear","probability_of_precipitation":20,"temperature":25.0,"feels_like":28.0,"humidity":80,"wind_speed":10.0,"wind_direction":180,"wind":{"compass_8":"S","compass_16":"S","compass_32":"S","arrow":"↑","beaufort":2,"beaufort_description":"light breeze"},"precipitation":0.2,"wind_gusts":25.0,"uv_index":6.0,"cloud_cover":40,"dew_point":12.0,"visibility":24140.0,"surface_pressure":1013.2,"solar_radiation":450.0,"stress":{"wbgt":24.5,"wbgt_risk":"red","heat_index":null,"heat_index_risk":"green","wind_chill":null,"wind_chill_risk":"green"},"recommendations":[],"course":[],"checkpoints":[],"daylight":null,"air_quality":null,"alerts":[],"ensemble":null}"#
         );
     }
 }
@@ -1,90 +1,316 @@
-use anyhow::{anyhow, Result};
+use serde::{self, Deserialize, Serialize};
 
-static DEGREES: &[i32] = &[0, 45, 90, 135, 180, 225, 270, 315, 360];
-static DIRECTIONS: &[&str] = &["N", "NE", "E", "SE", "S", "SW", "W", "NW", "N"];
+use crate::i18n::Locale;
 
-pub fn wind_direction(deg: i32) -> Result<String> {
-    if !(0..=360).contains(&deg) {
-        return Err(anyhow!(
-            "Wind direction outside of the range 0..360: {}",
-            deg
-        ));
+/// The 32 points of the compass, every other one being the 16-point compass.
+static POINTS: &[&str] = &[
+    "N", "NbE", "NNE", "NEbN", "NE", "NEbE", "ENE", "EbN", "E", "EbS", "ESE", "SEbE", "SE", "SEbS",
+    "SSE", "SbE", "S", "SbW", "SSW", "SWbS", "SW", "SWbW", "WSW", "WbS", "W", "WbN", "WNW", "NWbW",
+    "NW", "NWbN", "NNW", "NbW",
+];
+
+/// Arrows pointing where the wind blows toward, starting from north and going clockwise.
+static ARROWS: &[&str] = &["↑", "↗", "→", "↘", "↓", "↙", "←", "↖"];
+
+/// Upper limit of each Beaufort force in km/h, and its description; above them it is force 12.
+static BEAUFORT: &[(f64, &str)] = &[
+    (1.0, "calm"),
+    (6.0, "light air"),
+    (12.0, "light breeze"),
+    (20.0, "gentle breeze"),
+    (29.0, "moderate breeze"),
+    (39.0, "fresh breeze"),
+    (50.0, "strong breeze"),
+    (62.0, "near gale"),
+    (75.0, "gale"),
+    (89.0, "strong gale"),
+    (103.0, "storm"),
+    (118.0, "violent storm"),
+];
+
+/// Speed in km/h over which the fractional force goes half of the way from 11.5 to 12.5 in the
+/// open-ended hurricane force.
+const HURRICANE_SCALE: f64 = 15.0;
+
+/// Lower and upper limits in km/h of a Beaufort force below 12, with -1 for calm so that no wind
+/// is force 0.
+fn limits(force: usize) -> (f64, f64) {
+    let lower = match force {
+        0 => -1.0,
+        _ => BEAUFORT[force - 1].0,
+    };
+    (lower, BEAUFORT[force].0)
+}
+
+/// Fractional Beaufort force from a speed in km/h, interpolated in the same table as `beaufort`
+/// so that it rounds to the same force (the sign is kept, e.g. for a tailwind).
+pub fn force_from_kmh(kmh: f64) -> f64 {
+    let speed = kmh.abs();
+    let force = match BEAUFORT.iter().position(|(limit, _)| speed < *limit) {
+        Some(force) => {
+            let (lower, upper) = limits(force);
+            force as f64 - 0.5 + (speed - lower) / (upper - lower)
+        }
+        None => {
+            let top = BEAUFORT[BEAUFORT.len() - 1].0;
+            12.5 - HURRICANE_SCALE / (speed - top + HURRICANE_SCALE)
+        }
+    };
+    kmh.signum() * force
+}
+
+/// Speed in km/h from a fractional Beaufort force, the inverse of `force_from_kmh`.
+pub fn kmh_from_force(force: f64) -> f64 {
+    let value = force.abs();
+    let kmh = if value >= BEAUFORT.len() as f64 - 0.5 {
+        let top = BEAUFORT[BEAUFORT.len() - 1].0;
+        top - HURRICANE_SCALE + HURRICANE_SCALE / (12.5 - value).max(f64::EPSILON)
+    } else {
+        let force = (value + 0.5).floor() as usize;
+        let (lower, upper) = limits(force);
+        lower + (value - force as f64 + 0.5) * (upper - lower)
+    };
+    force.signum() * kmh
+}
+
+/// Closest point of a compass with 8, 16 or 32 points.
+pub fn compass(deg: i32, points: usize) -> String {
+    let step = 360.0 / points as f64;
+    let idx = (f64::from(deg.rem_euclid(360)) / step).round() as usize % points;
+    POINTS[idx * POINTS.len() / points].to_string()
+}
+
+/// Arrow for the direction the wind blows toward, the opposite of where it comes from.
+pub fn arrow(deg: i32) -> &'static str {
+    let toward = f64::from((deg + 180).rem_euclid(360));
+    ARROWS[(toward / 45.0).round() as usize % ARROWS.len()]
+}
+
+/// Beaufort force and its description from a wind speed in km/h.
+pub fn beaufort(kmh: f64) -> (u8, &'static str) {
+    match BEAUFORT.iter().position(|(limit, _)| kmh < *limit) {
+        Some(force) => (force as u8, BEAUFORT[force].1),
+        None => (12, "hurricane force"),
+    }
+}
+
+/// Every representation of the wind, for the JSON output.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Wind {
+    pub compass_8: String,
+    pub compass_16: String,
+    pub compass_32: String,
+    pub arrow: &'static str,
+    pub beaufort: u8,
+    pub beaufort_description: &'static str,
+}
+
+impl Wind {
+    /// `direction` is where the wind comes from in degrees, and `speed` is in km/h.
+    pub fn new(direction: i32, speed: f64) -> Self {
+        let (beaufort, beaufort_description) = beaufort(speed);
+        Self {
+            compass_8: compass(direction, 8),
+            compass_16: compass(direction, 16),
+            compass_32: compass(direction, 32),
+            arrow: arrow(direction),
+            beaufort,
+            beaufort_description,
+        }
     }
+}
+
+/// Points of the compass for the direction, 8, 16 or 32 in the configuration file.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(try_from = "u8")]
+pub enum Compass {
+    #[default]
+    Eight,
+    Sixteen,
+    ThirtyTwo,
+}
 
-    let closest = DEGREES.iter().min_by_key(|d| (deg - *d).abs());
-    match closest {
-        None => Err(anyhow!("No closest direction found for {}", deg)),
-        Some(degree) => {
-            let idx = DEGREES.iter().position(|d| d == degree).unwrap();
-            Ok(DIRECTIONS[idx].to_string())
+impl TryFrom<u8> for Compass {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            8 => Ok(Compass::Eight),
+            16 => Ok(Compass::Sixteen),
+            32 => Ok(Compass::ThirtyTwo),
+            _ => Err(format!("the compass has 8, 16 or 32 points, got {value}")),
         }
     }
 }
 
+impl Compass {
+    pub fn points(&self) -> usize {
+        match self {
+            Compass::Eight => 8,
+            Compass::Sixteen => 16,
+            Compass::ThirtyTwo => 32,
+        }
+    }
+}
+
+/// How the wind is described in the human-readable output.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Style {
+    /// Points of the compass for the direction
+    pub compass: Compass,
+
+    /// Adds an arrow for the direction the wind blows toward
+    pub arrow: bool,
+
+    /// Adds the Beaufort force and its description
+    pub beaufort: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            compass: Compass::Eight,
+            arrow: false,
+            beaufort: false,
+        }
+    }
+}
+
+impl Style {
+    /// Direction, arrow and Beaufort force as configured, e.g. `SSW ↑ (force 5, fresh breeze)`.
+    pub fn as_string(&self, wind: &Wind, locale: &Locale) -> String {
+        let direction = match self.compass {
+            Compass::Eight => &wind.compass_8,
+            Compass::Sixteen => &wind.compass_16,
+            Compass::ThirtyTwo => &wind.compass_32,
+        };
+        let mut output = locale.direction(direction);
+        if self.arrow {
+            output.push_str(&format!(" {}", wind.arrow));
+        }
+        if self.beaufort {
+            output.push_str(&format!(
+                " ({} {}, {})",
+                locale.t("force"),
+                wind.beaufort,
+                locale.t(wind.beaufort_description)
+            ));
+        }
+        output
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_wind_direction_north() {
+    fn test_compass_north() {
         let deg = 20;
-        let result = wind_direction(deg).unwrap();
+        let result = compass(deg, Compass::Eight.points());
         assert_eq!(result, "N");
     }
 
     #[test]
-    fn test_wind_direction_northeast() {
+    fn test_compass_northeast() {
         let deg = 30;
-        let result = wind_direction(deg).unwrap();
+        let result = compass(deg, Compass::Eight.points());
         assert_eq!(result, "NE");
     }
 
     #[test]
-    fn test_wind_direction_east() {
+    fn test_compass_east() {
         let deg = 80;
-        let result = wind_direction(deg).unwrap();
+        let result = compass(deg, Compass::Eight.points());
         assert_eq!(result, "E");
     }
 
     #[test]
-    fn test_wind_direction_southeast() {
+    fn test_compass_southeast() {
         let deg = 135;
-        let result = wind_direction(deg).unwrap();
+        let result = compass(deg, Compass::Eight.points());
         assert_eq!(result, "SE");
     }
 
     #[test]
-    fn test_wind_direction_south() {
+    fn test_compass_south() {
         let deg = 180;
-        let result = wind_direction(deg).unwrap();
+        let result = compass(deg, Compass::Eight.points());
         assert_eq!(result, "S");
     }
 
     #[test]
-    fn test_wind_direction_southwest() {
+    fn test_compass_southwest() {
         let deg = 225;
-        let result = wind_direction(deg).unwrap();
+        let result = compass(deg, Compass::Eight.points());
         assert_eq!(result, "SW");
     }
 
     #[test]
-    fn test_wind_direction_west() {
+    fn test_compass_west() {
         let deg = 270;
-        let result = wind_direction(deg).unwrap();
+        let result = compass(deg, Compass::Eight.points());
         assert_eq!(result, "W");
     }
 
     #[test]
-    fn test_wind_direction_northwest() {
+    fn test_compass_northwest() {
         let deg = 315;
-        let result = wind_direction(deg).unwrap();
+        let result = compass(deg, Compass::Eight.points());
         assert_eq!(result, "NW");
     }
 
     #[test]
-    fn test_wind_direction_invalid() {
-        let deg = 400;
-        let result = wind_direction(deg);
-        assert!(result.is_err());
+    fn test_compass() {
+        assert_eq!(compass(20, 8), "N");
+        assert_eq!(compass(20, 16), "NNE");
+        assert_eq!(compass(20, 32), "NNE");
+        assert_eq!(compass(10, 32), "NbE");
+        assert_eq!(compass(350, 16), "N");
+        assert_eq!(compass(250, 16), "WSW");
+        assert_eq!(compass(360, 32), "N");
+    }
+
+    #[test]
+    fn test_arrow() {
+        assert_eq!(arrow(180), "↑");
+        assert_eq!(arrow(0), "↓");
+        assert_eq!(arrow(225), "↗");
+        assert_eq!(arrow(90), "←");
+    }
+
+    #[test]
+    fn test_beaufort() {
+        assert_eq!(beaufort(0.5), (0, "calm"));
+        assert_eq!(beaufort(30.0), (5, "fresh breeze"));
+        assert_eq!(beaufort(74.0), (8, "gale"));
+        assert_eq!(beaufort(130.0), (12, "hurricane force"));
+    }
+
+    #[test]
+    fn test_style_compass() {
+        let style: Style = serde_json::from_str(r#"{"compass": 32}"#).unwrap();
+        assert_eq!(style.compass, Compass::ThirtyTwo);
+        assert!(serde_json::from_str::<Style>(r#"{"compass": 12}"#).is_err());
+    }
+
+    #[test]
+    fn test_style_as_string() {
+        let wind = Wind::new(200, 30.0);
+        assert_eq!(Style::default().as_string(&wind, &Locale::En), "S");
+        let style = Style {
+            compass: Compass::Sixteen,
+            arrow: true,
+            beaufort: true,
+        };
+        assert_eq!(
+            style.as_string(&wind, &Locale::En),
+            "SSW ↑ (force 5, fresh breeze)"
+        );
+        assert_eq!(
+            style.as_string(&wind, &Locale::Fr),
+            "SSO ↑ (force 5, bonne brise)"
+        );
     }
 }
@@ -0,0 +1,98 @@
+/// WMO 4677 weather codes used by Open-Meteo: description, and emoji for day and night.
+const CODES: &[(i8, &str, &str, &str)] = &[
+    (0, "clear sky", "☀️", "🌙"),
+    (1, "mainly clear", "☀️", "🌙"),
+    (2, "partly cloudy", "⛅", "☁️"),
+    (3, "overcast", "⛅", "☁️"),
+    (45, "fog", "☁️", "☁️"),
+    (48, "depositing rime fog", "☁️", "☁️"),
+    (51, "light drizzle", "🌧️", "🌧️"),
+    (53, "moderate drizzle", "🌧️", "🌧️"),
+    (55, "dense drizzle", "🌧️", "🌧️"),
+    (56, "light freezing drizzle", "🌧️", "🌧️"),
+    (57, "dense freezing drizzle", "🌧️", "🌧️"),
+    (61, "slight rain", "🌧️", "🌧️"),
+    (63, "moderate rain", "🌧️", "🌧️"),
+    (65, "heavy rain", "🌧️", "🌧️"),
+    (66, "light freezing rain", "🌧️", "🌧️"),
+    (67, "heavy freezing rain", "🌧️", "🌧️"),
+    (71, "slight snow fall", "🌨️", "🌨️"),
+    (73, "moderate snow fall", "🌨️", "🌨️"),
+    (75, "heavy snow fall", "🌨️", "🌨️"),
+    (77, "snow grains", "🌨️", "🌨️"),
+    (80, "slight rain showers", "🌧️", "🌧️"),
+    (81, "moderate rain showers", "🌧️", "🌧️"),
+    (82, "violent rain showers", "🌧️", "🌧️"),
+    (85, "slight snow showers", "🌨️", "🌨️"),
+    (86, "heavy snow showers", "🌨️", "🌨️"),
+    (95, "thunderstorm", "🌩️", "🌩️"),
+    (96, "thunderstorm with slight hail", "🌩️", "🌩️"),
+    (99, "thunderstorm with heavy hail", "🌩️", "🌩️"),
+];
+
+/// Codes missing from the table fall back to the group of the WMO 4677 code table they belong to.
+fn fallback(code: i8) -> (&'static str, &'static str) {
+    match code {
+        0..=19 => ("no precipitation", "⛅"),
+        20..=29 => ("precipitation in the past hour", "🌧️"),
+        30..=39 => ("duststorm, sandstorm or blowing snow", "🌬️"),
+        40..=49 => ("fog", "☁️"),
+        50..=59 => ("drizzle", "🌧️"),
+        60..=69 => ("rain", "🌧️"),
+        70..=79 => ("snow", "🌨️"),
+        80..=94 => ("showers", "🌧️"),
+        95..=99 => ("thunderstorm", "🌩️"),
+        _ => ("unknown weather", "🌡️"),
+    }
+}
+
+pub fn description(code: i8) -> &'static str {
+    match CODES.iter().find(|(value, ..)| *value == code) {
+        Some((_, description, ..)) => description,
+        None => fallback(code).0,
+    }
+}
+
+pub fn emoji(code: i8, is_day: bool) -> &'static str {
+    match CODES.iter().find(|(value, ..)| *value == code) {
+        Some((_, _, day, _)) if is_day => day,
+        Some((_, _, _, night)) => night,
+        None => fallback(code).1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_description() {
+        assert_eq!(description(56), "light freezing drizzle");
+        assert_eq!(description(99), "thunderstorm with heavy hail");
+    }
+
+    #[test]
+    fn test_description_fallback() {
+        assert_eq!(description(4), "no precipitation");
+        assert_eq!(description(68), "rain");
+        assert_eq!(description(100), "unknown weather");
+        assert_eq!(description(-1), "unknown weather");
+    }
+
+    #[test]
+    fn test_emoji_day_and_night() {
+        assert_eq!(emoji(0, true), "☀️");
+        assert_eq!(emoji(0, false), "🌙");
+        assert_eq!(emoji(61, false), "🌧️");
+        assert_eq!(emoji(100, true), "🌡️");
+    }
+
+    #[test]
+    fn test_emoji_by_group() {
+        assert_eq!(emoji(3, true), "⛅");
+        assert_eq!(emoji(46, true), "☁️");
+        assert_eq!(emoji(55, true), "🌧️");
+        assert_eq!(emoji(75, true), "🌨️");
+        assert_eq!(emoji(97, true), "🌩️");
+    }
+}